@@ -0,0 +1,68 @@
+//! Benchmarks for generating liturgical years and looking up dates in them.
+//! Run with `cargo bench`.
+
+use chrono::{Duration, NaiveDate};
+use criterion::{criterion_group, criterion_main, Criterion};
+use liturgical_calendar::{
+    compute_liturgical_year, generate_events, previous_event, CalendarCache, CalendarOptions,
+};
+use std::hint::black_box;
+
+const DECADE: std::ops::Range<i32> = 2020..2030;
+
+/// A cold generation of one year, against a lookup of a year already in the cache.
+fn one_year(c: &mut Criterion) {
+    let options = CalendarOptions::default();
+    c.bench_function("generate_events 2024", |b| {
+        b.iter(|| generate_events(black_box(2024), &options))
+    });
+    let mut cache = CalendarCache::new(options.clone());
+    cache.get_or_generate(2024).expect("2024 is in range");
+    c.bench_function("cached year 2024", |b| {
+        b.iter(|| cache.get_or_generate(black_box(2024)).map(<[_]>::len))
+    });
+}
+
+/// Ten consecutive years, as a multi-year listing generates them.
+fn decade(c: &mut Criterion) {
+    let options = CalendarOptions::default();
+    c.bench_function("generate_events decade", |b| {
+        b.iter(|| {
+            for year in DECADE {
+                black_box(generate_events(year, &options).expect("the decade is in range"));
+            }
+        })
+    });
+}
+
+/// One lookup per day of the decade against cached years, with `previous_event`'s
+/// binary search and with the linear scan it replaced.
+fn daily_lookups(c: &mut Criterion) {
+    let first = NaiveDate::from_ymd_opt(DECADE.start, 12, 1).expect("1 December exists");
+    let dates: Vec<NaiveDate> = (0..3650).map(|i| first + Duration::days(i)).collect();
+    let mut cache = CalendarCache::new(CalendarOptions::default());
+    c.bench_function("3650 daily lookups", |b| {
+        b.iter(|| {
+            for &date in &dates {
+                let events = cache
+                    .get_or_generate(compute_liturgical_year(date))
+                    .expect("the decade is in range");
+                black_box(previous_event(events, date));
+            }
+        })
+    });
+    c.bench_function("3650 daily lookups, linear scan", |b| {
+        b.iter(|| {
+            for &date in &dates {
+                let events = cache
+                    .get_or_generate(compute_liturgical_year(date))
+                    .expect("the decade is in range");
+                let exact = events.iter().find(|ev| ev.date == date);
+                black_box(exact.or_else(|| events.iter().rev().find(|ev| ev.date <= date)));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, one_year, decade, daily_lookups);
+criterion_main!(benches);
@@ -0,0 +1,3018 @@
+//! Computes the liturgical calendar used by the `pericope` command: the Sunday
+//! and feast events of a liturgical year, their seasons, altar colors and
+//! reading sets, and the readings files and configuration that adjust them.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+/// An event in the liturgical calendar.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub label: String,
+    /// ISO date, e.g. "2025-02-09".
+    pub date: NaiveDate,
+    pub altar_color: AltarColor,
+    /// Priority is used when two events fall on the same day;
+    /// higher priority events override lower ones.
+    pub priority: u8,
+    pub dating: Dating,
+}
+
+/// Whether an event's date moves from year to year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Dating {
+    /// Derived from Advent or Easter.
+    Movable,
+    /// Tied to a calendar date (e.g. Christmas).
+    Fixed,
+}
+
+/// The liturgical seasons, in the order they occur within a liturgical year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Season {
+    Advent,
+    Christmas,
+    Epiphany,
+    PreEaster,
+    Easter,
+    Pentecost,
+    Trinity,
+}
+
+impl Season {
+    /// Classifies an event label into its season.
+    pub fn from_label(label: &str) -> Option<Season> {
+        let season = if label.starts_with("advent") {
+            Season::Advent
+        } else if label.starts_with("christmas") {
+            Season::Christmas
+        } else if label.starts_with("epiphany") || matches!(label, "transfiguration" | "candlemas")
+        {
+            Season::Epiphany
+        } else if label.starts_with("easter - ")
+            || matches!(
+                label,
+                "ash wednesday" | "maundy thursday" | "good friday" | "annunciation"
+            )
+        {
+            Season::PreEaster
+        } else if label.starts_with("easter") || label == "ascension" {
+            Season::Easter
+        } else if label.starts_with("pentecost") {
+            Season::Pentecost
+        } else if label.starts_with("trinity")
+            || matches!(
+                label,
+                "corpus christi" | "michaelmas" | "reformation day" | "all saints"
+            )
+        {
+            Season::Trinity
+        } else {
+            return None;
+        };
+        Some(season)
+    }
+}
+
+impl Season {
+    /// Every season, in liturgical-year order.
+    pub const ALL: [Season; 7] = [
+        Season::Advent,
+        Season::Christmas,
+        Season::Epiphany,
+        Season::PreEaster,
+        Season::Easter,
+        Season::Pentecost,
+        Season::Trinity,
+    ];
+
+    /// A three-letter abbreviation for dense layouts such as the year chart.
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            Season::Advent => "Adv",
+            Season::Christmas => "Chr",
+            Season::Epiphany => "Epi",
+            Season::PreEaster => "Pre",
+            Season::Easter => "Eas",
+            Season::Pentecost => "Pen",
+            Season::Trinity => "Tri",
+        }
+    }
+}
+
+impl std::fmt::Display for Season {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Season::Advent => "Advent",
+            Season::Christmas => "Christmas",
+            Season::Epiphany => "Epiphany",
+            Season::PreEaster => "Pre-Easter",
+            Season::Easter => "Easter",
+            Season::Pentecost => "Pentecost",
+            Season::Trinity => "Trinity",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl std::str::FromStr for Season {
+    type Err = String;
+
+    /// Parses a season by its display name, ignoring case, e.g. "trinity" or "Pre-Easter".
+    fn from_str(name: &str) -> Result<Season, String> {
+        Season::ALL
+            .into_iter()
+            .find(|season| season.to_string().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| {
+                let names: Vec<String> = Season::ALL.iter().map(|s| s.to_string()).collect();
+                format!(
+                    "Unknown season \"{}\". Expected one of: {}.",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Labels of the principal feasts, as opposed to ordinary Sundays.
+const PRINCIPAL_FEASTS: [&str; 7] = [
+    "christmas",
+    "epiphany",
+    "easter vigil",
+    "easter",
+    "ascension",
+    "pentecost",
+    "trinity",
+];
+
+/// Traditional names of the pre-Easter Sundays, from Septuagesima to Palm Sunday,
+/// the Sunday opening Holy Week, and of the Sundays after Easter up to Exaudi,
+/// the Sunday after the Ascension.
+const TRADITIONAL_NAMES: [(&str, &str); 15] = [
+    ("easter - 9", "Septuagesima"),
+    ("easter - 8", "Sexagesima"),
+    ("easter - 7", "Quinquagesima"),
+    ("easter - 6", "Invocavit"),
+    ("easter - 5", "Reminiscere"),
+    ("easter - 4", "Oculi"),
+    ("easter - 3", "Laetare"),
+    ("easter - 2", "Judica"),
+    ("easter - 1", "Palm Sunday"),
+    ("easter + 1", "Quasimodogeniti"),
+    ("easter + 2", "Misericordias Domini"),
+    ("easter + 3", "Jubilate"),
+    ("easter + 4", "Cantate"),
+    ("easter + 5", "Rogate"),
+    ("easter + 6", "Exaudi"),
+];
+
+/// The traditional name of a Sunday label such as "easter - 1", if it has one.
+/// The numeric labels stay the keys of events and readings; this is for display only.
+pub fn traditional_name(label: &str) -> Option<&'static str> {
+    TRADITIONAL_NAMES
+        .iter()
+        .find(|(known, _)| *known == label)
+        .map(|(_, name)| *name)
+}
+
+impl Event {
+    /// The season this event belongs to.
+    pub fn season(&self) -> Option<Season> {
+        Season::from_label(&self.label)
+    }
+
+    /// Whether this event keeps a date it shares with `other`: the higher priority wins,
+    /// and on equal priority the alphabetically first label, so the outcome does not
+    /// depend on the order in which the series are generated.
+    pub fn outranks(&self, other: &Event) -> bool {
+        (self.priority, Reverse(&self.label)) > (other.priority, Reverse(&other.label))
+    }
+
+    /// Whether this event is one of the `PRINCIPAL_FEASTS` rather than an ordinary Sunday.
+    pub fn is_principal(&self) -> bool {
+        PRINCIPAL_FEASTS.contains(&self.label.as_str())
+    }
+}
+
+/// Reads a user-supplied text file, cleaned up by `normalize_text`.
+/// `kind` names the file in error messages (e.g. "readings").
+pub fn read_text_file(path: &str, kind: &str) -> Result<String, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read {} file {}: {}", kind, path, e))?;
+    Ok(normalize_text(&content))
+}
+
+/// Strips a leading UTF-8 byte order mark and normalizes CRLF line endings, as left
+/// behind by some Windows editors.
+pub fn normalize_text(content: &str) -> String {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    content.replace("\r\n", "\n")
+}
+
+/// The altar colors known to the calendar.
+/// Rose is not used by the generated calendar but may be chosen in palettes and overrides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AltarColor {
+    Purple,
+    White,
+    Green,
+    Red,
+    Rose,
+}
+
+impl AltarColor {
+    /// Every altar color, in the order they are listed in messages.
+    pub const ALL: [AltarColor; 5] = [
+        AltarColor::Purple,
+        AltarColor::White,
+        AltarColor::Green,
+        AltarColor::Red,
+        AltarColor::Rose,
+    ];
+
+    /// The lowercase name used in files and text output, e.g. "purple".
+    pub fn name(self) -> &'static str {
+        match self {
+            AltarColor::Purple => "purple",
+            AltarColor::White => "white",
+            AltarColor::Green => "green",
+            AltarColor::Red => "red",
+            AltarColor::Rose => "rose",
+        }
+    }
+
+    /// Built-in hex value, used when no palette overrides it.
+    pub fn builtin_hex(self) -> &'static str {
+        match self {
+            AltarColor::Purple => "#663399",
+            AltarColor::White => "#ffffff",
+            AltarColor::Green => "#2e7d32",
+            AltarColor::Red => "#c62828",
+            AltarColor::Rose => "#e8a0b8",
+        }
+    }
+}
+
+impl std::fmt::Display for AltarColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses a user-supplied color name, as written in palettes, config files and arguments.
+impl std::str::FromStr for AltarColor {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<AltarColor, String> {
+        AltarColor::ALL
+            .into_iter()
+            .find(|color| color.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = AltarColor::ALL.iter().map(|c| c.name()).collect();
+                format!(
+                    "Unknown altar color \"{}\". Expected one of: {}.",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Normalizes a `#rgb` or `#rrggbb` hex value to lowercase `#rrggbb`.
+fn normalize_hex(value: &str) -> Option<String> {
+    let digits = value.trim().strip_prefix('#')?;
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match digits.len() {
+        6 => Some(format!("#{}", digits.to_ascii_lowercase())),
+        3 => Some(digits.chars().fold(String::from("#"), |mut hex, c| {
+            hex.push(c.to_ascii_lowercase());
+            hex.push(c.to_ascii_lowercase());
+            hex
+        })),
+        _ => None,
+    }
+}
+
+/// A user-provided mapping from altar color names to hex values.
+/// Colors missing from the palette fall back to the built-in values.
+#[derive(Debug, Default)]
+pub struct Palette {
+    colors: HashMap<AltarColor, String>,
+}
+
+impl Palette {
+    /// Loads a palette from a TOML file of `color = "#rrggbb"` entries.
+    pub fn load(path: &str) -> Result<Palette, String> {
+        let content = read_text_file(path, "palette")?;
+        let raw: HashMap<String, String> = toml::from_str(&content)
+            .map_err(|e| format!("Unable to parse palette file {}: {}", path, e))?;
+        let mut colors = HashMap::new();
+        for (name, value) in raw {
+            let color = name
+                .parse::<AltarColor>()
+                .map_err(|e| format!("{} (palette file {})", e, path))?;
+            let hex = normalize_hex(&value).ok_or_else(|| {
+                format!(
+                    "Invalid hex value {:?} for color {:?} in palette file {}",
+                    value, name, path
+                )
+            })?;
+            colors.insert(color, hex);
+        }
+        Ok(Palette { colors })
+    }
+
+    /// Returns the hex value for a color, preferring the palette over the built-in mapping.
+    pub fn hex(&self, color: AltarColor) -> String {
+        self.colors
+            .get(&color)
+            .cloned()
+            .unwrap_or_else(|| color.builtin_hex().to_string())
+    }
+
+    /// The colors of `used` that the palette does not define, sorted by name and deduplicated.
+    pub fn missing(&self, used: impl IntoIterator<Item = AltarColor>) -> Vec<AltarColor> {
+        let mut missing: Vec<AltarColor> = used
+            .into_iter()
+            .filter(|color| !self.colors.contains_key(color))
+            .collect();
+        missing.sort_unstable_by_key(|color| color.name());
+        missing.dedup();
+        missing
+    }
+}
+
+/// A stable identifier for a liturgical year, e.g. "2024-2025" for Advent 2024 onwards.
+pub fn liturgical_year_id(lit_year: i32) -> String {
+    format!("{}-{}", lit_year, lit_year + 1)
+}
+
+/// A stable, URL-safe identifier for an event, e.g. "2024-2025/trinity%2B5".
+pub fn event_id(lit_year: i32, label: &str) -> String {
+    format!(
+        "{}/{}",
+        liturgical_year_id(lit_year),
+        encode_path_segment(label)
+    )
+}
+
+/// Percent-encodes a label for use as a URL path segment. The spaces around
+/// the "+"/"-" offset operators are dropped first, so "trinity + 5" becomes
+/// "trinity%2B5" and "easter - 9" becomes "easter-9".
+pub fn encode_path_segment(label: &str) -> String {
+    let compact = label.replace(" + ", "+").replace(" - ", "-");
+    let mut encoded = String::with_capacity(compact.len());
+    for byte in compact.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// The serialized form of an event, carrying both its civil date and its
+/// liturgical coordinates so timeline and chart frontends need no calendar logic.
+#[derive(Debug, Serialize)]
+pub struct EventRecord {
+    pub id: String,
+    /// ISO date, e.g. "2025-02-09".
+    pub date: NaiveDate,
+    /// The same date as dd/mm/yyyy, matching the text output.
+    pub civil_date: String,
+    pub label: String,
+    pub color: AltarColor,
+    pub hex: String,
+    pub season: Option<Season>,
+    /// Zero-based position of the event within its season, read from its label
+    /// ("advent + 2" → 2, "easter - 9" → 0) so that feasts in between do not shift
+    /// it. A feast outside the series counts whole weeks on from the series event before it.
+    pub season_index: usize,
+    /// One-based week of the liturgical year, counted from Advent I.
+    pub week: i64,
+    /// Signed whole weeks from Easter, e.g. -3 for "easter - 3".
+    pub weeks_from_easter: i64,
+    /// Whole weeks from Advent I before the pre-Easter series, from Easter after.
+    #[serde(flatten)]
+    pub week_offset: WeekOffset,
+    pub is_principal: bool,
+    pub dating: Dating,
+}
+
+/// Builds the serialized records of a liturgical year's events, computing the
+/// derived fields once for every output that needs them.
+pub fn event_records(
+    lit_year: i32,
+    events: Vec<Event>,
+    palette: &Palette,
+    options: &CalendarOptions,
+) -> Vec<EventRecord> {
+    let anchors = year_anchors(lit_year, options).expect("the events were generated for this year");
+    let start = anchors.advent;
+    let series = options.series.clone().unwrap_or_else(Series::builtin);
+    // A series counting down to its anchor ("easter - 9" to "easter - 1") starts
+    // with its largest offset.
+    let mut deepest: HashMap<&str, i64> = HashMap::new();
+    for (base, offset) in events.iter().filter_map(|ev| label_offset(&ev.label)) {
+        if offset < 0 {
+            let depth = deepest.entry(base).or_default();
+            *depth = (*depth).max(-offset);
+        }
+    }
+    let mut records: Vec<EventRecord> = Vec::with_capacity(events.len());
+    let mut last_series_event: HashMap<Option<Season>, (usize, NaiveDate)> = HashMap::new();
+    for ev in &events {
+        let season = ev.season();
+        let series_offset = label_offset(&ev.label).filter(|&(base, offset)| {
+            offset != 0 || series.iter().any(|series| series.name == base)
+        });
+        let season_index = match series_offset {
+            Some((base, offset)) if offset < 0 => (deepest[base] + offset) as usize,
+            Some((_, offset)) => offset as usize,
+            // A feast outside the series counts on from the season's last series event,
+            // so a feast displacing "trinity + 22" gets 22.
+            None => match last_series_event.get(&season) {
+                Some(&(index, date)) => index + ((ev.date - date).num_days() / 7) as usize,
+                None => 0,
+            },
+        };
+        if series_offset.is_some() {
+            last_series_event.insert(season, (season_index, ev.date));
+        }
+        records.push(EventRecord {
+            id: event_id(lit_year, &ev.label),
+            date: ev.date,
+            civil_date: ev.date.format("%d/%m/%Y").to_string(),
+            hex: palette.hex(ev.altar_color),
+            week: (ev.date - start).num_days() / 7 + 1,
+            weeks_from_easter: anchors.weeks_from_easter(ev.date),
+            week_offset: anchors.week_offset(ev.date),
+            is_principal: ev.is_principal(),
+            dating: ev.dating,
+            season,
+            season_index,
+            label: ev.label.clone(),
+            color: ev.altar_color,
+        });
+    }
+    records
+}
+
+/// A contiguous run of days belonging to one season, with its event markers.
+/// `start` is the first event of the season and `end` the last day before the
+/// next season begins (or the last day of the liturgical year).
+#[derive(Debug, Serialize)]
+pub struct SeasonBand {
+    pub season: Season,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub events: Vec<EventRecord>,
+}
+
+/// Groups the events of a liturgical year into season bands, ready for charting.
+pub fn season_bands(
+    lit_year: i32,
+    options: &CalendarOptions,
+    palette: &Palette,
+) -> Result<Vec<SeasonBand>, CalendarError> {
+    let events = generate_events(lit_year, options)?;
+    let year_end = first_sunday_of_advent(lit_year + 1)
+        .expect("generate_events checked the following year")
+        - Duration::days(1);
+    let mut bands: Vec<SeasonBand> = Vec::new();
+    for record in event_records(lit_year, events, palette, options) {
+        let Some(season) = record.season else {
+            continue;
+        };
+        match bands.last_mut() {
+            Some(band) if band.season == season => band.events.push(record),
+            _ => {
+                if let Some(previous) = bands.last_mut() {
+                    previous.end = record.date - Duration::days(1);
+                }
+                bands.push(SeasonBand {
+                    season,
+                    start: record.date,
+                    end: year_end,
+                    events: vec![record],
+                });
+            }
+        }
+    }
+    Ok(bands)
+}
+
+/// Computes the First Sunday of Advent for a given year.
+/// In this calendar, the first Advent Sunday is defined as the first Sunday on or after November 21.
+pub fn first_sunday_of_advent(year: i32) -> Result<NaiveDate, CalendarError> {
+    let candidate =
+        NaiveDate::from_ymd_opt(year, 11, 21).ok_or(CalendarError::YearOutOfRange(year))?;
+    let offset = (7 - candidate.weekday().num_days_from_sunday()) % 7;
+    Ok(candidate + Duration::days(offset as i64))
+}
+
+/// Returns the first Sunday on or after the given date.
+pub fn first_sunday_on_or_after(mut date: NaiveDate) -> NaiveDate {
+    while date.weekday() != Weekday::Sun {
+        date += Duration::days(1);
+    }
+    date
+}
+
+/// Computes the date of Easter for the given year (using the Meeus/Jones/Butcher algorithm).
+pub fn compute_easter(year: i32) -> Result<NaiveDate, CalendarError> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31; // 3 = March, 4 = April
+    let day = ((h + l - 7 * m + 114) % 31) + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+        .ok_or(CalendarError::YearOutOfRange(year))
+}
+
+/// Ash Wednesday of the given year, 46 days before Easter.
+pub fn ash_wednesday(year: i32) -> Result<NaiveDate, CalendarError> {
+    Ok(compute_easter(year)? - Duration::days(46))
+}
+
+/// Maundy Thursday of the given year, 3 days before Easter.
+pub fn maundy_thursday(year: i32) -> Result<NaiveDate, CalendarError> {
+    Ok(compute_easter(year)? - Duration::days(3))
+}
+
+/// Good Friday of the given year, 2 days before Easter.
+pub fn good_friday(year: i32) -> Result<NaiveDate, CalendarError> {
+    Ok(compute_easter(year)? - Duration::days(2))
+}
+
+/// Ascension Day of the given year, 39 days after Easter.
+pub fn ascension_day(year: i32) -> Result<NaiveDate, CalendarError> {
+    Ok(compute_easter(year)? + Duration::days(39))
+}
+
+/// Computes the date of Orthodox Easter (Pascha) for the given year (Meeus' Julian
+/// algorithm), converted to the Gregorian calendar.
+pub fn compute_easter_julian(year: i32) -> Result<NaiveDate, CalendarError> {
+    let a = year % 4;
+    let b = year % 7;
+    let c = year % 19;
+    let d = (19 * c + 15) % 30;
+    let e = (2 * a + 4 * b - d + 34) % 7;
+    let month = (d + e + 114) / 31;
+    let day = ((d + e + 114) % 31) + 1;
+    // Days the Julian calendar lags the Gregorian one in March and April of `year`:
+    // one more for each century year the Gregorian calendar skips as a leap year
+    // (13 in 1900-2099, 14 in 2100-2199).
+    let julian_lag = year / 100 - year / 400 - 2;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+        .and_then(|date| date.checked_add_signed(Duration::days(julian_lag as i64)))
+        .ok_or(CalendarError::YearOutOfRange(year))
+}
+
+/// Which Easter computation the calendar is built around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EasterReckoning {
+    /// Gregorian Easter, as computed by `compute_easter`.
+    #[default]
+    Western,
+    /// Julian-reckoned Pascha, as computed by `compute_easter_julian`.
+    Orthodox,
+}
+
+impl EasterReckoning {
+    /// The first civil year whose Easter this reckoning computes meaningfully: 1583,
+    /// the first full year of the Gregorian calendar, for `Western`, and 326, after
+    /// the Council of Nicaea fixed the Julian computus, for `Orthodox`.
+    pub fn first_valid_year(self) -> i32 {
+        match self {
+            EasterReckoning::Western => 1583,
+            EasterReckoning::Orthodox => 326,
+        }
+    }
+
+    /// The date of Easter in civil year `year` under this reckoning.
+    pub fn easter(self, year: i32) -> Result<NaiveDate, CalendarError> {
+        match self {
+            EasterReckoning::Western => compute_easter(year),
+            EasterReckoning::Orthodox => compute_easter_julian(year),
+        }
+    }
+}
+
+/// Optional observances and adjustments that `generate_events` applies to the Sunday series.
+#[derive(Debug, Clone, Default)]
+pub struct CalendarOptions {
+    /// Add the Easter Vigil and the Vigil of Pentecost (the Saturdays before each feast).
+    pub include_vigils: bool,
+    /// Add Corpus Christi, the Thursday after Trinity Sunday.
+    pub include_corpus_christi: bool,
+    /// Add the weekday feasts fixed relative to Easter: Ash Wednesday, Maundy Thursday,
+    /// Good Friday and Ascension Day.
+    pub include_movable_feasts: bool,
+    /// Add the fixed-date feasts of Epiphany (6 January), Candlemas (2 February), the
+    /// Annunciation (25 March), Michaelmas (29 September), Reformation Day (31 October)
+    /// and All Saints' Day (1 November), and the Transfiguration on the last Sunday
+    /// before the pre-Easter series. Their colors can be changed by label overrides.
+    pub include_fixed_feasts: bool,
+    /// Generate years whose Easter predates the reckoning's `first_valid_year`
+    /// instead of rejecting them.
+    pub allow_historical: bool,
+    /// Reject equal-priority collisions instead of resolving them silently.
+    pub strict: bool,
+    /// The Easter that the Pre-Easter, Easter, Pentecost and Trinity series follow.
+    pub easter_reckoning: EasterReckoning,
+    /// Local color changes applied after generation, e.g. red for a church dedication.
+    pub color_overrides: Vec<ColorOverride>,
+    /// Date ranges in which a fixed-date feast beats a movable event regardless of priority.
+    pub fixed_feast_windows: Vec<FeastWindow>,
+    /// The Sunday series to generate, or `None` for `Series::builtin`.
+    pub series: Option<Vec<Series>>,
+}
+
+impl CalendarOptions {
+    /// Whether the fixed-feast windows decide a collision between `a` and `b`:
+    /// exactly one of them is a fixed-date feast and their date lies in a window.
+    pub fn fixed_feast_rule_applies(&self, a: &Event, b: &Event) -> bool {
+        a.dating != b.dating
+            && self
+                .fixed_feast_windows
+                .iter()
+                .any(|window| window.contains(a.date))
+    }
+}
+
+/// A recurring range of days, given as (month, day) bounds, e.g. 25/12 to 06/01.
+/// Ranges whose end precedes their start wrap around the new year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeastWindow {
+    pub start: (u32, u32),
+    pub end: (u32, u32),
+}
+
+impl FeastWindow {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        let day = (date.month(), date.day());
+        if self.start <= self.end {
+            self.start <= day && day <= self.end
+        } else {
+            day >= self.start || day <= self.end
+        }
+    }
+}
+
+/// Parses a "dd/mm" day of the year, as used by fixed-feast windows.
+fn parse_day_month(text: &str) -> Option<(u32, u32)> {
+    let (day, month) = text.trim().split_once('/')?;
+    let (day, month) = (day.parse().ok()?, month.parse().ok()?);
+    // 2000 is a leap year, so 29/02 is accepted.
+    NaiveDate::from_ymd_opt(2000, month, day).map(|_| (month, day))
+}
+
+/// Which events a color override applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverrideTarget {
+    Date(NaiveDate),
+    Label(String),
+}
+
+/// Replaces the altar color of the events matching `target`, leaving the event itself unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorOverride {
+    pub target: OverrideTarget,
+    pub color: AltarColor,
+}
+
+/// The date a Sunday series is counted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeriesAnchor {
+    /// Advent I. As the end of a series (`until`) it is the next year's Advent I.
+    Advent,
+    /// 25 December, the only anchor on a fixed date rather than a Sunday.
+    Christmas,
+    /// The second Sunday after Christmas. It falls between 2 and 8 January, before the
+    /// first pre-Easter Sunday (at the earliest 18 January, for Easter on 22 March).
+    Epiphany,
+    Easter,
+    Pentecost,
+    /// The Sunday after Pentecost.
+    Trinity,
+}
+
+impl SeriesAnchor {
+    /// The anchor's date in the liturgical year `lit_year` with the given anchors.
+    pub fn date(self, lit_year: i32, anchors: &YearAnchors) -> NaiveDate {
+        let christmas =
+            NaiveDate::from_ymd_opt(lit_year, 12, 25).expect("25 December exists in every year");
+        match self {
+            SeriesAnchor::Advent => anchors.advent,
+            SeriesAnchor::Christmas => christmas,
+            SeriesAnchor::Epiphany => {
+                first_sunday_on_or_after(christmas + Duration::days(1)) + Duration::days(7)
+            }
+            SeriesAnchor::Easter => anchors.easter,
+            SeriesAnchor::Pentecost => anchors.pentecost,
+            SeriesAnchor::Trinity => anchors.trinity,
+        }
+    }
+}
+
+/// Which way a series counts from its anchor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeriesDirection {
+    /// The anchor itself ("easter"), then the Sundays after it ("easter + 1", ...).
+    #[default]
+    After,
+    /// The Sundays before the anchor ("easter - 1", "easter - 2", ...).
+    Before,
+}
+
+/// How many events a series has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeriesLength {
+    Count(i64),
+    /// Every event dated before this anchor, e.g. Advent Sundays until Christmas.
+    Until(SeriesAnchor),
+}
+
+/// A series of weekly events labeled by their offset from an anchor, e.g. "trinity + 5".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Series {
+    pub name: String,
+    pub anchor: SeriesAnchor,
+    pub direction: SeriesDirection,
+    pub length: SeriesLength,
+    /// Colors of the events counted outward from the anchor; the last one repeats.
+    pub colors: Vec<AltarColor>,
+    /// Decides collisions with other events: the higher priority keeps the date.
+    pub priority: u8,
+}
+
+impl Series {
+    /// The built-in series structure, used when no config file defines one.
+    pub fn builtin() -> Vec<Series> {
+        use AltarColor::{Green, Purple, Red, White};
+        let series =
+            |name: &str, anchor, direction, length, colors: &[AltarColor], priority| Series {
+                name: name.to_string(),
+                anchor,
+                direction,
+                length,
+                colors: colors.to_vec(),
+                priority,
+            };
+        vec![
+            // Advent I up to the last Sunday before Christmas: five Sundays, or four
+            // when Advent I falls on 27 November and a fifth would be Christmas.
+            series(
+                "advent",
+                SeriesAnchor::Advent,
+                SeriesDirection::After,
+                SeriesLength::Until(SeriesAnchor::Christmas),
+                &[Purple],
+                1,
+            ),
+            series(
+                "christmas",
+                SeriesAnchor::Christmas,
+                SeriesDirection::After,
+                SeriesLength::Count(2),
+                &[White],
+                2,
+            ),
+            series(
+                "epiphany",
+                SeriesAnchor::Epiphany,
+                SeriesDirection::After,
+                SeriesLength::Count(7),
+                &[White, Green],
+                3,
+            ),
+            series(
+                "easter",
+                SeriesAnchor::Easter,
+                SeriesDirection::Before,
+                SeriesLength::Count(9),
+                &[White, White, Purple, Purple, Purple, Purple, White, Green],
+                4,
+            ),
+            series(
+                "easter",
+                SeriesAnchor::Easter,
+                SeriesDirection::After,
+                SeriesLength::Count(7),
+                &[White],
+                5,
+            ),
+            series(
+                "pentecost",
+                SeriesAnchor::Pentecost,
+                SeriesDirection::After,
+                SeriesLength::Count(1),
+                &[Red],
+                6,
+            ),
+            // Every Sunday up to the next Advent: 22 Sundays after the latest Easter
+            // and 27 after the earliest.
+            series(
+                "trinity",
+                SeriesAnchor::Trinity,
+                SeriesDirection::After,
+                SeriesLength::Until(SeriesAnchor::Advent),
+                &[White, Green, Green, Green, Green, Red, Green],
+                7,
+            ),
+        ]
+    }
+
+    /// The events of the series in `lit_year`. An `After` series puts its first event
+    /// on the anchor and the rest on the following Sundays; a `Before` series uses
+    /// the Sundays before the anchor. Nothing runs past the end of the year.
+    pub fn events(&self, lit_year: i32, anchors: &YearAnchors) -> Vec<Event> {
+        let anchor = self.anchor.date(lit_year, anchors);
+        let until = match self.length {
+            SeriesLength::Count(_) => anchors.end,
+            SeriesLength::Until(SeriesAnchor::Advent) => anchors.end,
+            SeriesLength::Until(limit) => limit.date(lit_year, anchors).min(anchors.end),
+        };
+        let count = match self.length {
+            SeriesLength::Count(count) => count,
+            SeriesLength::Until(_) => i64::MAX,
+        };
+        let mut events = Vec::new();
+        for i in 0..count {
+            let (label, date, dating) = match self.direction {
+                SeriesDirection::After if i == 0 => {
+                    let dating = if self.anchor == SeriesAnchor::Christmas {
+                        Dating::Fixed
+                    } else {
+                        Dating::Movable
+                    };
+                    (self.name.clone(), anchor, dating)
+                }
+                SeriesDirection::After => (
+                    format!("{} + {}", self.name, i),
+                    first_sunday_on_or_after(anchor + Duration::days(1))
+                        + Duration::days(7 * (i - 1)),
+                    Dating::Movable,
+                ),
+                SeriesDirection::Before => {
+                    let back = match anchor.weekday().num_days_from_sunday() {
+                        0 => 7,
+                        days => days as i64,
+                    };
+                    (
+                        format!("{} - {}", self.name, i + 1),
+                        anchor - Duration::days(back + 7 * i),
+                        Dating::Movable,
+                    )
+                }
+            };
+            if date >= until {
+                break;
+            }
+            let color = self.colors[(i as usize).min(self.colors.len() - 1)];
+            events.push(Event {
+                label,
+                date,
+                altar_color: color,
+                priority: self.priority,
+                dating,
+            });
+        }
+        events
+    }
+}
+
+/// Checks that a series structure opens the liturgical year: some series must count
+/// from Advent I, so that its first event falls on the first day of the year.
+pub fn validate_series(series: &[Series]) -> Result<(), CalendarError> {
+    let opens_year = series.iter().any(|series| {
+        series.anchor == SeriesAnchor::Advent
+            && series.direction == SeriesDirection::After
+            && series.length != SeriesLength::Count(0)
+    });
+    if opens_year {
+        Ok(())
+    } else {
+        Err(CalendarError::MissingAdventSeries)
+    }
+}
+
+/// One `[[series]]` table of a config file; exactly one of `count` or `until` is set.
+#[derive(Debug, Deserialize)]
+struct SeriesEntry {
+    name: String,
+    anchor: SeriesAnchor,
+    #[serde(default)]
+    direction: SeriesDirection,
+    count: Option<i64>,
+    until: Option<SeriesAnchor>,
+    colors: Vec<String>,
+    priority: Option<u8>,
+}
+
+/// One `[[fixed_feast_window]]` table of a config file.
+#[derive(Debug, Deserialize)]
+struct FeastWindowEntry {
+    start: String,
+    end: String,
+}
+
+/// One `[[color_override]]` table of a config file; exactly one of `date` or `label` is set.
+#[derive(Debug, Deserialize)]
+struct ColorOverrideEntry {
+    date: Option<String>,
+    label: Option<String>,
+    color: String,
+}
+
+/// The layout of a calendar config file, e.g.
+///
+/// ```toml
+/// [[color_override]]
+/// date = "20/07/2025"
+/// color = "red"
+///
+/// [[color_override]]
+/// label = "trinity + 8"
+/// color = "white"
+///
+/// [[fixed_feast_window]]
+/// start = "25/12"
+/// end = "01/01"
+///
+/// [[series]]
+/// name = "easter"
+/// anchor = "easter"
+/// direction = "before"
+/// count = 9
+/// colors = ["white", "white", "purple", "purple", "purple", "purple", "white", "green"]
+///
+/// [[series]]
+/// name = "trinity"
+/// anchor = "trinity"
+/// until = "advent"
+/// colors = ["white", "green"]
+/// ```
+///
+/// `[[series]]` tables, if any, replace the whole built-in series structure; one of
+/// them must count from Advent I (`anchor = "advent"`). A series' priority defaults
+/// to its position in the file, so later series win collisions.
+#[derive(Debug, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    color_override: Vec<ColorOverrideEntry>,
+    #[serde(default)]
+    fixed_feast_window: Vec<FeastWindowEntry>,
+    #[serde(default)]
+    series: Vec<SeriesEntry>,
+}
+
+/// The settings read from a calendar config file.
+#[derive(Debug, Default)]
+pub struct CalendarConfig {
+    pub color_overrides: Vec<ColorOverride>,
+    pub fixed_feast_windows: Vec<FeastWindow>,
+    /// The series structure, if the file defines one.
+    pub series: Option<Vec<Series>>,
+}
+
+/// Loads a calendar config file, validating its dates and colors.
+pub fn load_config(path: &str) -> Result<CalendarConfig, String> {
+    let content = read_text_file(path, "config")?;
+    let file: ConfigFile = toml::from_str(&content)
+        .map_err(|e| format!("Unable to parse config file {}: {}", path, e))?;
+    let fixed_feast_windows = file
+        .fixed_feast_window
+        .iter()
+        .map(
+            |entry| match (parse_day_month(&entry.start), parse_day_month(&entry.end)) {
+                (Some(start), Some(end)) => Ok(FeastWindow { start, end }),
+                _ => Err(format!(
+                    "Invalid fixed feast window {:?} to {:?} in {}; use dd/mm.",
+                    entry.start, entry.end, path
+                )),
+            },
+        )
+        .collect::<Result<Vec<_>, String>>()?;
+    let color_overrides = file
+        .color_override
+        .into_iter()
+        .map(|entry| {
+            let color = entry.color.parse::<AltarColor>()?;
+            let target = match (entry.date, entry.label) {
+                (Some(date), None) => OverrideTarget::Date(
+                    NaiveDate::parse_from_str(&date, "%d/%m/%Y").map_err(|_| {
+                        format!(
+                            "Invalid color override date {:?} in {}; use dd/mm/yyyy.",
+                            date, path
+                        )
+                    })?,
+                ),
+                (None, Some(label)) => OverrideTarget::Label(label),
+                _ => {
+                    return Err(format!(
+                        "Each color override in {} needs exactly one of `date` or `label`.",
+                        path
+                    ))
+                }
+            };
+            Ok(ColorOverride { target, color })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let series = file
+        .series
+        .into_iter()
+        .enumerate()
+        .map(|(position, entry)| {
+            let length = match (entry.count, entry.until, entry.direction) {
+                (Some(count), None, _) if count > 0 => SeriesLength::Count(count),
+                (None, Some(until), SeriesDirection::After) => SeriesLength::Until(until),
+                _ => {
+                    return Err(format!(
+                        "Series {:?} in {} needs either a positive `count` or, counting \
+                         after its anchor, an `until` anchor.",
+                        entry.name, path
+                    ))
+                }
+            };
+            if entry.colors.is_empty() {
+                return Err(format!(
+                    "Series {:?} in {} needs at least one color.",
+                    entry.name, path
+                ));
+            }
+            let colors = entry
+                .colors
+                .iter()
+                .map(|color| color.parse::<AltarColor>())
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok(Series {
+                name: entry.name,
+                anchor: entry.anchor,
+                direction: entry.direction,
+                length,
+                colors,
+                priority: entry
+                    .priority
+                    .unwrap_or_else(|| u8::try_from(position + 1).unwrap_or(u8::MAX)),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if !series.is_empty() {
+        validate_series(&series).map_err(|e| format!("Invalid series in {}: {}.", path, e))?;
+    }
+    Ok(CalendarConfig {
+        color_overrides,
+        fixed_feast_windows,
+        series: (!series.is_empty()).then_some(series),
+    })
+}
+
+/// Errors reported while generating the calendar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CalendarError {
+    /// Two events claim the same date at the same priority (strict mode only).
+    Collision {
+        date: NaiveDate,
+        existing: String,
+        incoming: String,
+        priority: u8,
+    },
+    /// The year lies outside the range of dates the calendar can represent.
+    YearOutOfRange(i32),
+    /// The liturgical year starts inside the supported range but ends, with the
+    /// following year's Easter and Advent, outside it.
+    FollowingYearOutOfRange { lit_year: i32 },
+    /// The year's Easter falls before the first year the Easter computation is valid for.
+    HistoricalYear { year: i32, first_valid_year: i32 },
+    /// No series opens the year on Advent I, so the dates before its first event
+    /// would have no pericope.
+    MissingAdventSeries,
+}
+
+impl std::fmt::Display for CalendarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CalendarError::Collision {
+                date,
+                existing,
+                incoming,
+                priority,
+            } => write!(
+                f,
+                "\"{}\" and \"{}\" both fall on {} at priority {}",
+                existing,
+                incoming,
+                date.format("%d/%m/%Y"),
+                priority
+            ),
+            CalendarError::YearOutOfRange(year) => {
+                write!(f, "year {} is outside the supported range", year)
+            }
+            CalendarError::FollowingYearOutOfRange { lit_year } => write!(
+                f,
+                "liturgical year {} ends in {}, which is outside the supported range",
+                lit_year,
+                i64::from(*lit_year) + 1
+            ),
+            CalendarError::HistoricalYear {
+                year,
+                first_valid_year,
+            } => write!(
+                f,
+                "Easter {} is outside the valid range of the Easter computation, which starts in {}",
+                year, first_valid_year
+            ),
+            CalendarError::MissingAdventSeries => write!(
+                f,
+                "no series counts from Advent I, so the liturgical year has no opening event"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CalendarError {}
+
+/// The dates a liturgical year's series are computed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct YearAnchors {
+    /// Advent I, which opens the year.
+    pub advent: NaiveDate,
+    /// Easter in the following civil year, under the configured reckoning.
+    pub easter: NaiveDate,
+    /// Seven weeks after Easter.
+    pub pentecost: NaiveDate,
+    /// The Sunday after Pentecost, which opens the Trinity series.
+    pub trinity: NaiveDate,
+    /// Advent I of the next liturgical year: the first day after this one.
+    pub end: NaiveDate,
+}
+
+/// How far an event lies from the anchor it is usually reckoned from. Serialized
+/// as `"anchor": "advent", "offset": 5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "anchor", content = "offset")]
+pub enum WeekOffset {
+    /// Whole weeks after Advent I, for events before the pre-Easter series.
+    #[serde(rename = "advent")]
+    FromAdvent(i64),
+    /// Signed whole weeks from Easter, for everything from the pre-Easter series on.
+    #[serde(rename = "easter")]
+    FromEaster(i64),
+}
+
+impl YearAnchors {
+    /// Signed whole weeks from Easter to `date`, e.g. -3 for "easter - 3" and 8 for
+    /// "trinity". A weekday counts in the week of the Sunday before it, so Good Friday
+    /// is in week -1 and Ascension Day in week 5.
+    pub fn weeks_from_easter(&self, date: NaiveDate) -> i64 {
+        (date - self.easter).num_days().div_euclid(7)
+    }
+
+    /// The offset of `date` from Advent I up to the "easter - 9" Sunday, and from
+    /// Easter from then on.
+    pub fn week_offset(&self, date: NaiveDate) -> WeekOffset {
+        if date < self.easter - Duration::days(7 * 9) {
+            WeekOffset::FromAdvent((date - self.advent).num_days().div_euclid(7))
+        } else {
+            WeekOffset::FromEaster(self.weeks_from_easter(date))
+        }
+    }
+}
+
+/// Computes the anchor dates of `lit_year`, as used by `generate_events`.
+pub fn year_anchors(
+    lit_year: i32,
+    options: &CalendarOptions,
+) -> Result<YearAnchors, CalendarError> {
+    let advent = first_sunday_of_advent(lit_year)?;
+    // Advent I of `lit_year` exists, so only the following year can be out of range.
+    let following = |error| match error {
+        CalendarError::YearOutOfRange(_) => CalendarError::FollowingYearOutOfRange { lit_year },
+        error => error,
+    };
+    let next_year = lit_year
+        .checked_add(1)
+        .ok_or(CalendarError::FollowingYearOutOfRange { lit_year })?;
+    let first_valid_year = options.easter_reckoning.first_valid_year();
+    if next_year < first_valid_year && !options.allow_historical {
+        return Err(CalendarError::HistoricalYear {
+            year: next_year,
+            first_valid_year,
+        });
+    }
+    let easter = options
+        .easter_reckoning
+        .easter(next_year)
+        .map_err(following)?;
+    let pentecost = easter + Duration::days(49);
+    Ok(YearAnchors {
+        advent,
+        easter,
+        pentecost,
+        trinity: pentecost + Duration::days(7),
+        end: first_sunday_of_advent(next_year).map_err(following)?,
+    })
+}
+
+/// The built-in Christmas and Epiphany series of `lit_year`. "christmas" is fixed to
+/// 25 December and "christmas + 1" is the first Sunday after it. The Sunday after
+/// that opens the Epiphany series, whether it falls before 6 January (2 to 5 January)
+/// or is the first Sunday on or after it (6 to 8 January), so Epiphany always starts
+/// one week after "christmas + 1" and runs for seven Sundays.
+pub fn christmas_and_epiphany_events(lit_year: i32) -> Result<Vec<Event>, CalendarError> {
+    let anchors = year_anchors(lit_year, &CalendarOptions::default())?;
+    Ok(Series::builtin()
+        .iter()
+        .filter(|series| matches!(series.name.as_str(), "christmas" | "epiphany"))
+        .flat_map(|series| series.events(lit_year, &anchors))
+        .collect())
+}
+
+/// Generates all events for the given liturgical year.
+/// The liturgical year runs from the First Sunday of Advent of the given year
+/// until (but not including) the First Sunday of Advent of the next year.
+/// Optional observances are added as enabled in `options`.
+pub fn generate_events(
+    lit_year: i32,
+    options: &CalendarOptions,
+) -> Result<Vec<Event>, CalendarError> {
+    let anchors = year_anchors(lit_year, options)?;
+    let (start, end) = (anchors.advent, anchors.end);
+
+    let mut events_map: HashMap<NaiveDate, Event> = HashMap::new();
+    let mut strict_error: Option<CalendarError> = None;
+
+    // Helper: insert an event if its date falls between [start, end).
+    // On a collision the higher-priority event replaces the other entirely, so the
+    // displaced event's label (and with it its readings key) disappears; equal
+    // priorities are settled by `Event::outranks`, never by insertion order. Inside a
+    // configured fixed-feast window a fixed-date feast wins regardless of priority.
+    // In strict mode the first equal-priority collision is recorded.
+    let mut insert_event = |ev: Event| {
+        if ev.date >= start && ev.date < end {
+            if options.strict && strict_error.is_none() {
+                if let Some(existing) = events_map.get(&ev.date) {
+                    if existing.priority == ev.priority
+                        && !options.fixed_feast_rule_applies(&ev, existing)
+                    {
+                        strict_error = Some(CalendarError::Collision {
+                            date: ev.date,
+                            existing: existing.label.clone(),
+                            incoming: ev.label.clone(),
+                            priority: ev.priority,
+                        });
+                    }
+                }
+            }
+            events_map
+                .entry(ev.date)
+                .and_modify(|existing| {
+                    let replace = if options.fixed_feast_rule_applies(&ev, existing) {
+                        ev.dating == Dating::Fixed
+                    } else {
+                        ev.outranks(existing)
+                    };
+                    if replace {
+                        *existing = ev.clone();
+                    }
+                })
+                .or_insert(ev);
+        }
+    };
+
+    // 1.-7. The Sunday series: Advent, Christmas, Epiphany, pre-Easter, Easter,
+    // Pentecost and Trinity, unless the config file defines its own.
+    let builtin_series;
+    let series = match &options.series {
+        Some(series) => series,
+        None => {
+            builtin_series = Series::builtin();
+            &builtin_series
+        }
+    };
+    for ev in series
+        .iter()
+        .flat_map(|series| series.events(lit_year, &anchors))
+    {
+        insert_event(ev);
+    }
+    let (easter, pentecost, trinity_start) = (anchors.easter, anchors.pentecost, anchors.trinity);
+
+    // 8. Vigils (Saturday evenings before Easter and Pentecost), priority = 8.
+    if options.include_vigils {
+        let vigils = [
+            (
+                "easter vigil",
+                easter - Duration::days(1),
+                AltarColor::White,
+            ),
+            (
+                "pentecost vigil",
+                pentecost - Duration::days(1),
+                AltarColor::Red,
+            ),
+        ];
+        for (label, date, color) in vigils {
+            insert_event(Event {
+                label: label.to_string(),
+                date,
+                altar_color: color,
+                priority: 8,
+                dating: Dating::Movable,
+            });
+        }
+    }
+
+    // 9. Corpus Christi (Thursday after Trinity, Easter + 60 days, white), priority = 8.
+    if options.include_corpus_christi {
+        insert_event(Event {
+            label: "corpus christi".to_string(),
+            date: trinity_start + Duration::days(4),
+            altar_color: AltarColor::White,
+            priority: 8,
+            dating: Dating::Movable,
+        });
+    }
+
+    // 10. Weekday feasts around Easter, priority = 8. They follow `easter`, so the
+    // Orthodox reckoning moves them too.
+    if options.include_movable_feasts {
+        let feasts = [
+            ("ash wednesday", -46, AltarColor::Purple),
+            ("maundy thursday", -3, AltarColor::White),
+            ("good friday", -2, AltarColor::Red),
+            ("ascension", 39, AltarColor::White),
+        ];
+        // Ascension Day is the Thursday ten days before Pentecost, between "easter + 5"
+        // and "easter + 6", which stays white as the last Sunday of the Easter season.
+        for (label, offset, color) in feasts {
+            insert_event(Event {
+                label: label.to_string(),
+                date: easter + Duration::days(offset),
+                altar_color: color,
+                priority: 8,
+                dating: Dating::Movable,
+            });
+        }
+    }
+
+    // 11. Fixed-date feasts, mostly priority = 8, so they also displace a Sunday they
+    // fall on. Candlemas (9) can fall on the Transfiguration and wins. The Annunciation
+    // has priority 0 instead: it yields to every other event, the Lent and Easter
+    // Sundays included, and is dropped in Holy Week (Palm Sunday to Holy Saturday),
+    // whose weekdays have no event of their own to outrank it.
+    // Each is tried in both civil years the window touches; `insert_event` keeps
+    // only the dates inside [start, end).
+    if options.include_fixed_feasts {
+        let feasts = [
+            ("epiphany day", 1, 6, AltarColor::White, 8),
+            ("candlemas", 2, 2, AltarColor::White, 9),
+            ("annunciation", 3, 25, AltarColor::White, 0),
+            ("michaelmas", 9, 29, AltarColor::White, 8),
+            ("reformation day", 10, 31, AltarColor::Red, 8),
+            ("all saints", 11, 1, AltarColor::White, 8),
+        ];
+        let holy_week = easter - Duration::days(7)..easter;
+        for year in [lit_year, lit_year + 1] {
+            for (label, month, day, color, priority) in feasts {
+                let date = NaiveDate::from_ymd_opt(year, month, day)
+                    .expect("fixed feasts fall on days that exist in every year");
+                if label == "annunciation" && holy_week.contains(&date) {
+                    continue;
+                }
+                insert_event(Event {
+                    label: label.to_string(),
+                    date,
+                    altar_color: color,
+                    priority,
+                    dating: Dating::Fixed,
+                });
+            }
+        }
+
+        // The Transfiguration (white) replaces the last Sunday before "easter - 9",
+        // which is always in the Epiphany series: at the earliest 11 January, after
+        // the Epiphany Sunday, and at the latest 14 February.
+        insert_event(Event {
+            label: "transfiguration".to_string(),
+            date: easter - Duration::days(7 * 10),
+            altar_color: AltarColor::White,
+            priority: 8,
+            dating: Dating::Movable,
+        });
+    }
+
+    if let Some(e) = strict_error {
+        return Err(e);
+    }
+
+    let mut events: Vec<Event> = events_map.into_values().collect();
+    events.sort_by_key(|ev| ev.date);
+    for ev in &mut events {
+        for color_override in &options.color_overrides {
+            let matches = match &color_override.target {
+                OverrideTarget::Date(date) => ev.date == *date,
+                OverrideTarget::Label(label) => ev.label == *label,
+            };
+            if matches {
+                ev.altar_color = color_override.color;
+            }
+        }
+    }
+    // `insert_event` only keeps dates inside [start, end); fail loudly in debug builds
+    // if a future series (e.g. a late-November fixed feast) ever bypasses it.
+    debug_assert!(
+        events.iter().all(|ev| ev.date >= start && ev.date < end),
+        "liturgical year {} produced an event outside [{}, {})",
+        lit_year,
+        start,
+        end
+    );
+    // Every date inside [start, end) needs an event on or before it. `load_config`
+    // rejects series structures without an Advent series, but `options.series` can
+    // also be built by hand.
+    if events.first().map(|ev| ev.date) != Some(start) {
+        return Err(CalendarError::MissingAdventSeries);
+    }
+    Ok(events)
+}
+
+/// Splits a label into its base and signed week offset: "trinity + 5" → ("trinity", 5),
+/// "easter - 3" → ("easter", -3), and a label without an offset → (label, 0).
+/// `None` if the offset is not a number.
+fn label_offset(label: &str) -> Option<(&str, i64)> {
+    match label.split_once(" + ") {
+        Some((base, n)) => Some((base, n.parse::<i64>().ok()?)),
+        None => match label.split_once(" - ") {
+            Some((base, n)) => Some((base, -n.parse::<i64>().ok()?)),
+            None => Some((label, 0)),
+        },
+    }
+}
+
+/// The documented color of a built-in label, written out independently of
+/// `Series::builtin` and the feast tables so `validate_events` can check them.
+/// Labels of custom series have no rule.
+fn expected_color(label: &str) -> Option<AltarColor> {
+    use AltarColor::{Green, Purple, Red, White};
+    let (base, offset) = label_offset(label)?;
+    let color = match (base, offset) {
+        ("advent", 0..=4) => Purple,
+        ("christmas", 0..=1) => White,
+        ("epiphany", 0) => White,
+        ("epiphany", 1..=6) => Green,
+        ("easter", -9..=-8) => Green,
+        ("easter", -7) => White,
+        ("easter", -6..=-3) => Purple,
+        ("easter", -2..=6) => White,
+        ("pentecost", 0) => Red,
+        ("trinity", 0) => White,
+        ("trinity", 5) => Red,
+        ("trinity", 1..) => Green,
+        ("easter vigil" | "maundy thursday" | "ascension" | "corpus christi", 0) => White,
+        ("epiphany day" | "candlemas" | "annunciation" | "michaelmas", 0) => White,
+        ("all saints" | "transfiguration", 0) => White,
+        ("pentecost vigil" | "good friday" | "reformation day", 0) => Red,
+        ("ash wednesday", 0) => Purple,
+        _ => return None,
+    };
+    Some(color)
+}
+
+/// Checks the colors of generated events against the documented rule for each
+/// built-in label: purple Advent, white Christmas and Easter, red Pentecost, and so
+/// on. Events from color overrides are reported too; labels of custom series are skipped.
+pub fn validate_events(events: &[Event]) -> Result<(), String> {
+    let deviations: Vec<String> = events
+        .iter()
+        .filter_map(|ev| {
+            let expected = expected_color(&ev.label)?;
+            (ev.altar_color != expected).then(|| {
+                format!(
+                    "\"{}\" on {} is {} but should be {}",
+                    ev.label,
+                    ev.date.format("%d/%m/%Y"),
+                    ev.altar_color,
+                    expected
+                )
+            })
+        })
+        .collect();
+    if deviations.is_empty() {
+        Ok(())
+    } else {
+        Err(deviations.join("\n"))
+    }
+}
+
+/// Returns every event date of a liturgical year that uses the given altar color.
+pub fn dates_with_color(
+    lit_year: i32,
+    options: &CalendarOptions,
+    color: AltarColor,
+) -> Result<Vec<NaiveDate>, CalendarError> {
+    Ok(generate_events(lit_year, options)?
+        .into_iter()
+        .filter(|ev| ev.altar_color == color)
+        .map(|ev| ev.date)
+        .collect())
+}
+
+/// Generated liturgical years keyed by year, so repeated lookups in the same year
+/// reuse the events instead of generating them again.
+#[derive(Debug, Default)]
+pub struct CalendarCache {
+    options: CalendarOptions,
+    years: HashMap<i32, Vec<Event>>,
+}
+
+impl CalendarCache {
+    pub fn new(options: CalendarOptions) -> Self {
+        CalendarCache {
+            options,
+            years: HashMap::new(),
+        }
+    }
+
+    /// The options every cached year is generated with.
+    pub fn options(&self) -> &CalendarOptions {
+        &self.options
+    }
+
+    /// The events of liturgical year `year`, generated on the first request only.
+    pub fn get_or_generate(&mut self, year: i32) -> Result<&[Event], CalendarError> {
+        let events = match self.years.entry(year) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(generate_events(year, &self.options)?),
+        };
+        Ok(events)
+    }
+}
+
+/// The events of one liturgical year, for callers that want to scan them with
+/// iterator adapters. Events are generated once, when the year is built, since
+/// collisions can only be resolved over the whole year.
+#[derive(Debug, Clone)]
+pub struct LiturgicalYear {
+    pub year: i32,
+    events: Vec<Event>,
+}
+
+impl LiturgicalYear {
+    pub fn new(year: i32, options: &CalendarOptions) -> Result<Self, CalendarError> {
+        Ok(LiturgicalYear {
+            year,
+            events: generate_events(year, options)?,
+        })
+    }
+
+    /// The year's events in date order.
+    pub fn events(&self) -> impl Iterator<Item = &Event> {
+        self.events.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a LiturgicalYear {
+    type Item = &'a Event;
+    type IntoIter = std::slice::Iter<'a, Event>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.events.iter()
+    }
+}
+
+impl IntoIterator for LiturgicalYear {
+    type Item = Event;
+    type IntoIter = std::vec::IntoIter<Event>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.events.into_iter()
+    }
+}
+
+/// Liturgical years searched by `known_label`: a full 400-year Gregorian cycle, in which
+/// Easter takes every possible date, so every series reaches its longest length.
+const KNOWN_LABEL_YEARS: std::ops::RangeInclusive<i32> = 1800..=2199;
+
+/// Returns the canonical form of `label` if some liturgical year generates it under
+/// `options`, matching case-insensitively, e.g. "Trinity + 12" → "trinity + 12".
+pub fn known_label(label: &str, options: &CalendarOptions) -> Option<String> {
+    let label = label.trim();
+    KNOWN_LABEL_YEARS.into_iter().find_map(|lit_year| {
+        generate_events(lit_year, options)
+            .ok()?
+            .into_iter()
+            .find(|ev| ev.label.eq_ignore_ascii_case(label))
+            .map(|ev| ev.label)
+    })
+}
+
+/// Returns the date of the event labelled `label` in `lit_year`, or `None` when
+/// that year has no such event (e.g. an Epiphany Sunday cut short by an early Lent).
+/// Labels are matched case-insensitively.
+pub fn date_for_label(
+    lit_year: i32,
+    label: &str,
+    options: &CalendarOptions,
+) -> Result<Option<NaiveDate>, CalendarError> {
+    let label = label.trim();
+    Ok(LiturgicalYear::new(lit_year, options)?
+        .into_iter()
+        .find(|ev| ev.label.eq_ignore_ascii_case(label))
+        .map(|ev| ev.date))
+}
+
+/// Returns the latest event on or before `date`, using a binary search over
+/// events sorted by date (as returned by `generate_events`).
+pub fn previous_event(events: &[Event], date: NaiveDate) -> Option<&Event> {
+    let after = events.partition_point(|ev| ev.date <= date);
+    after.checked_sub(1).map(|i| &events[i])
+}
+
+/// Returns the first event strictly after `date` among events sorted by date.
+pub fn next_event(events: &[Event], date: NaiveDate) -> Option<&Event> {
+    let after = events.partition_point(|ev| ev.date <= date);
+    events.get(after)
+}
+
+/// Whether an event falls exactly on `date`, among events sorted by date.
+pub fn is_event_date(events: &[Event], date: NaiveDate) -> bool {
+    previous_event(events, date).is_some_and(|ev| ev.date == date)
+}
+
+/// Whether one of the principal feasts falls on `date`, among events sorted by date.
+pub fn has_principal_feast(events: &[Event], date: NaiveDate) -> bool {
+    previous_event(events, date).is_some_and(|ev| ev.date == date && ev.is_principal())
+}
+
+/// Returns the first event after `date`, moving on to Advent I of the following
+/// liturgical year when `date` falls after the last event of its own year.
+pub fn next_event_from(date: NaiveDate, options: &CalendarOptions) -> Result<Event, CalendarError> {
+    let lit_year = compute_liturgical_year(date);
+    let events = generate_events(lit_year, options)?;
+    if let Some(ev) = next_event(&events, date) {
+        return Ok(ev.clone());
+    }
+    let following = generate_events(lit_year + 1, options)?;
+    Ok(following
+        .into_iter()
+        .next()
+        .expect("every liturgical year opens with Advent I"))
+}
+
+/// The liturgical season of `date`: the season of the most recent event on or
+/// before it, so a weekday between the last Sunday of one season and the first
+/// Sunday of the next belongs to the earlier season.
+pub fn season_for_date(
+    date: NaiveDate,
+    options: &CalendarOptions,
+) -> Result<Option<Season>, CalendarError> {
+    let events = generate_events(compute_liturgical_year(date), options)?;
+    Ok(previous_event(&events, date).and_then(Event::season))
+}
+
+/// Counts the events of a liturgical year that fall on a Sunday. The total varies
+/// with the date of Easter, which shifts the Epiphany and Trinity series.
+pub fn sunday_count(lit_year: i32, options: &CalendarOptions) -> Result<usize, CalendarError> {
+    Ok(generate_events(lit_year, options)?
+        .iter()
+        .filter(|ev| ev.date.weekday() == Weekday::Sun)
+        .count())
+}
+
+/// Counts the Sunday events of `season` in a liturgical year, e.g. the Sundays after
+/// Trinity, whose number varies with the date of Easter.
+pub fn season_sunday_count(
+    lit_year: i32,
+    season: Season,
+    options: &CalendarOptions,
+) -> Result<usize, CalendarError> {
+    Ok(generate_events(lit_year, options)?
+        .iter()
+        .filter(|ev| ev.date.weekday() == Weekday::Sun && ev.season() == Some(season))
+        .count())
+}
+
+/// Counts the Sunday events of a liturgical year by altar color, after collisions and
+/// color overrides, so the counts match the listed year. Colors without a Sunday are absent.
+pub fn color_histogram(
+    lit_year: i32,
+    options: &CalendarOptions,
+) -> Result<HashMap<AltarColor, usize>, CalendarError> {
+    let mut histogram = HashMap::new();
+    for ev in generate_events(lit_year, options)? {
+        if ev.date.weekday() == Weekday::Sun {
+            *histogram.entry(ev.altar_color).or_insert(0) += 1;
+        }
+    }
+    Ok(histogram)
+}
+
+/// Collects the events falling within civil year `year` (1 January to 31 December).
+/// A civil year spans the tail of liturgical year `year - 1` and the first weeks
+/// of liturgical year `year`, so both are generated and filtered by date.
+pub fn civil_year_events(
+    year: i32,
+    options: &CalendarOptions,
+) -> Result<Vec<Event>, CalendarError> {
+    let previous_year = year
+        .checked_sub(1)
+        .ok_or(CalendarError::YearOutOfRange(year))?;
+    let mut events = generate_events(previous_year, options)?;
+    events.extend(generate_events(year, options)?);
+    events.retain(|ev| ev.date.year() == year);
+    Ok(events)
+}
+
+/// Collects the events falling between `start` and `end` inclusive, generating every
+/// liturgical year the range touches. Each year covers its own Advent-to-Advent window,
+/// so the merged list is already in date order with one event per date.
+pub fn events_in_range(
+    start: NaiveDate,
+    end: NaiveDate,
+    options: &CalendarOptions,
+) -> Result<Vec<Event>, CalendarError> {
+    let mut events = Vec::new();
+    for lit_year in compute_liturgical_year(start)..=compute_liturgical_year(end) {
+        events.extend(generate_events(lit_year, options)?);
+    }
+    events.retain(|ev| ev.date >= start && ev.date <= end);
+    Ok(events)
+}
+
+/// Determines the liturgical year for an input date.
+/// If the input date is on or after the First Sunday of Advent for that calendar year,
+/// the liturgical year is the calendar year; otherwise it is the previous calendar year.
+///
+/// The boundary is inclusive: the First Sunday of Advent itself opens the new liturgical
+/// year (e.g. 24/11/2024 → 2024), while the Saturday before it still closes the old one
+/// (23/11/2024 → 2023). This matches `generate_events`, whose window is `[start, end)`.
+pub fn compute_liturgical_year(input: NaiveDate) -> i32 {
+    let candidate =
+        first_sunday_of_advent(input.year()).expect("a date's own year is always in range");
+    if input >= candidate {
+        input.year()
+    } else {
+        input.year() - 1
+    }
+}
+
+/// The number of sets in the reading cycle.
+pub const SET_COUNT: i32 = 3;
+
+/// The liturgical year whose Advent begins Set I in our tradition.
+pub const DEFAULT_SET_ANCHOR: i32 = 2024;
+
+/// The rotation of the lectionary sets: the liturgical year whose Advent begins
+/// Set I, and how many sets the cycle has before it repeats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetCycle {
+    pub anchor: i32,
+    pub length: i32,
+}
+
+impl Default for SetCycle {
+    fn default() -> Self {
+        SetCycle {
+            anchor: DEFAULT_SET_ANCHOR,
+            length: SET_COUNT,
+        }
+    }
+}
+
+impl SetCycle {
+    /// The set of `lit_year`, from 1 to `length`. The distance to the anchor is taken
+    /// in `i64`, so no anchor and year overflow it.
+    pub fn set(self, lit_year: i32) -> i32 {
+        let distance = i64::from(lit_year) - i64::from(self.anchor);
+        let index = distance.rem_euclid(i64::from(self.length));
+        i32::try_from(index).expect("the remainder is smaller than `length`") + 1
+    }
+
+    /// The set whose gospel is read alongside `set`'s other readings: the next set in
+    /// the cycle, wrapping from the last back to Set I. A one-year cycle keeps Set I.
+    pub fn gospel_set(self, set: i32) -> i32 {
+        set.rem_euclid(self.length) + 1
+    }
+
+    /// Whether `set` is one of the cycle's sets.
+    pub fn contains(self, set: i32) -> bool {
+        (1..=self.length).contains(&set)
+    }
+}
+
+/// Computes the set number from the liturgical year.
+/// According to our rule:
+///   Advent 2024 → set I, 2025 → set II, 2026 → set III, then repeat.
+pub fn compute_set(lit_year: i32) -> i32 {
+    compute_set_anchored(lit_year, DEFAULT_SET_ANCHOR)
+}
+
+/// Computes the set number for traditions whose three-year cycle starts Set I in
+/// liturgical year `anchor` instead of `DEFAULT_SET_ANCHOR`.
+pub fn compute_set_anchored(lit_year: i32, anchor: i32) -> i32 {
+    SetCycle {
+        anchor,
+        length: SET_COUNT,
+    }
+    .set(lit_year)
+}
+
+/// The set whose gospel is read alongside `set`'s other readings: the next set in
+/// the cycle, wrapping from the last back to Set I (1 → 2, 2 → 3, 3 → 1).
+pub fn gospel_set(set: i32) -> i32 {
+    SetCycle::default().gospel_set(set)
+}
+
+/// Custom Bible readings.
+/// Key: (event label, set number)
+/// Value: (Old Testament, Lection, Gospel, Preaching)
+pub type CustomReadings = HashMap<(String, i32), (String, String, String, String)>;
+
+/// The four readings appointed for an event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Readings {
+    pub old_testament: String,
+    pub lection: String,
+    pub gospel: String,
+    pub preaching: String,
+}
+
+impl From<(String, String, String, String)> for Readings {
+    fn from((old_testament, lection, gospel, preaching): (String, String, String, String)) -> Self {
+        Readings {
+            old_testament,
+            lection,
+            gospel,
+            preaching,
+        }
+    }
+}
+
+/// A source of readings keyed by event label and set number, so the readings can
+/// come from the bundled table, a file, or a database.
+pub trait ReadingProvider {
+    /// The readings for `label` in `set`, or `None` when the provider has no entry.
+    fn readings(&self, label: &str, set: i32) -> Option<Readings>;
+}
+
+/// Readings looked up in a `CustomReadings` map.
+#[derive(Debug, Default)]
+pub struct HashMapReadingProvider {
+    readings: CustomReadings,
+}
+
+impl HashMapReadingProvider {
+    pub fn new(readings: CustomReadings) -> Self {
+        HashMapReadingProvider { readings }
+    }
+}
+
+impl ReadingProvider for HashMapReadingProvider {
+    fn readings(&self, label: &str, set: i32) -> Option<Readings> {
+        self.readings
+            .get(&(label.to_string(), set))
+            .cloned()
+            .map(Readings::from)
+    }
+}
+
+/// Descriptive placeholder readings for any label, e.g. "Gospel reading for advent
+/// (Set 2)". The gospel is taken from the next set in `cycle`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultReadingProvider {
+    pub cycle: SetCycle,
+}
+
+impl ReadingProvider for DefaultReadingProvider {
+    fn readings(&self, label: &str, set: i32) -> Option<Readings> {
+        Some(Readings {
+            old_testament: format!("Old Testament reading for {} (Set {})", label, set),
+            lection: format!("Lection reading for {} (Set {})", label, set),
+            gospel: format!(
+                "Gospel reading for {} (Set {})",
+                label,
+                self.cycle.gospel_set(set)
+            ),
+            preaching: format!("Preaching reading for {} (Set {})", label, set),
+        })
+    }
+}
+
+/// The readings for `label` in `set`: the `custom` entry if there is one, else the
+/// `DefaultReadingProvider` placeholders for the three-set cycle.
+pub fn readings_for(label: &str, set: i32, custom: &CustomReadings) -> Readings {
+    custom
+        .get(&(label.to_string(), set))
+        .cloned()
+        .map(Readings::from)
+        .or_else(|| DefaultReadingProvider::default().readings(label, set))
+        .expect("placeholder readings exist for every label")
+}
+
+/// The readings file bundled with the program.
+const BUNDLED_READINGS: &str = include_str!("readings.toml");
+
+/// The custom Bible readings bundled with the program, parsed from `readings.toml`.
+pub fn default_readings() -> CustomReadings {
+    parse_readings(BUNDLED_READINGS, "the bundled readings")
+        .expect("the bundled readings file is valid")
+}
+
+/// Checks whether `(label, set)` is a usable custom-reading key for `lit_year`:
+/// the label must occur among that year's events under `options` and the set must
+/// belong to `cycle`.
+pub fn is_valid_reading_key(
+    label: &str,
+    set: i32,
+    lit_year: i32,
+    options: &CalendarOptions,
+    cycle: SetCycle,
+) -> bool {
+    cycle.contains(set)
+        && generate_events(lit_year, options)
+            .is_ok_and(|events| events.iter().any(|ev| ev.label == label))
+}
+
+/// One entry of a readings file.
+#[derive(Debug, Deserialize)]
+struct ReadingEntry {
+    label: String,
+    set: i32,
+    old_testament: String,
+    lection: String,
+    gospel: String,
+    preaching: String,
+}
+
+/// The layout of a readings file: a list of `[[reading]]` tables, e.g.
+///
+/// ```toml
+/// [[reading]]
+/// label = "epiphany + 5"
+/// set = 1
+/// old_testament = "Jer 17:5-10"
+/// lection = "Col 3:12-17"
+/// gospel = "Mat 13:31-35"
+/// preaching = "Mat 13:24-30"
+/// ```
+#[derive(Debug, Deserialize)]
+struct ReadingsFile {
+    #[serde(default)]
+    reading: Vec<ReadingEntry>,
+}
+
+/// Loads custom Bible readings from a TOML readings file. Entries must use a set
+/// number from 1 up and may not repeat a (label, set) key; sets beyond the cycle
+/// in use are never looked up.
+pub fn load_readings(path: &str) -> Result<CustomReadings, String> {
+    let content = read_text_file(path, "readings")?;
+    parse_readings(&content, path)
+}
+
+/// Parses the contents of a readings file; `path` names it in error messages.
+fn parse_readings(content: &str, path: &str) -> Result<CustomReadings, String> {
+    let file: ReadingsFile = toml::from_str(content)
+        .map_err(|e| format!("Unable to parse readings file {}: {}", path, e))?;
+    let mut readings = CustomReadings::new();
+    for r in file.reading {
+        if r.set < 1 {
+            return Err(format!(
+                "Reading for \"{}\" in {} has set {}; sets are numbered from 1",
+                r.label, path, r.set
+            ));
+        }
+        let key = (r.label, r.set);
+        if readings.contains_key(&key) {
+            return Err(format!(
+                "Duplicate reading for \"{}\" (Set {}) in {}",
+                key.0, key.1, path
+            ));
+        }
+        readings.insert(key, (r.old_testament, r.lection, r.gospel, r.preaching));
+    }
+    Ok(readings)
+}
+
+/// The differences between a user's readings and the bundled defaults.
+/// Each list of keys is sorted by label, then set.
+#[derive(Debug, Default)]
+pub struct ReadingsDiff {
+    pub only_user: Vec<(String, i32)>,
+    pub only_default: Vec<(String, i32)>,
+    pub changed: Vec<(String, i32)>,
+}
+
+/// Compares a user's readings with the defaults, key by key.
+pub fn diff_readings(user: &CustomReadings, defaults: &CustomReadings) -> ReadingsDiff {
+    let mut diff = ReadingsDiff::default();
+    for (key, readings) in user {
+        match defaults.get(key) {
+            None => diff.only_user.push(key.clone()),
+            Some(default) if default != readings => diff.changed.push(key.clone()),
+            Some(_) => {}
+        }
+    }
+    diff.only_default = defaults
+        .keys()
+        .filter(|key| !user.contains_key(*key))
+        .cloned()
+        .collect();
+    diff.only_user.sort();
+    diff.only_default.sort();
+    diff.changed.sort();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32, month: u32, year: i32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// Writes `content` to a file unique to the calling test and returns its path.
+    fn temp_file(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(format!("pericope-{}-{}", std::process::id(), name));
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn config_without_advent_series_is_rejected() {
+        let path = temp_file(
+            "no-advent.toml",
+            "[[series]]\nname = \"easter\"\nanchor = \"easter\"\nuntil = \"advent\"\ncolors = [\"white\"]\n",
+        );
+        let error = load_config(&path).unwrap_err();
+        assert!(error.contains("Advent I"), "{}", error);
+    }
+
+    #[test]
+    fn hand_built_series_without_advent_is_an_error() {
+        let series = Series::builtin()
+            .into_iter()
+            .filter(|series| series.anchor != SeriesAnchor::Advent)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            validate_series(&series),
+            Err(CalendarError::MissingAdventSeries)
+        );
+        let options = CalendarOptions {
+            series: Some(series),
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_events(2024, &options).unwrap_err(),
+            CalendarError::MissingAdventSeries
+        );
+    }
+
+    #[test]
+    fn builtin_christmas_and_epiphany_series_keep_their_shape() {
+        for lit_year in 1900..=2200 {
+            let anchors = year_anchors(lit_year, &CalendarOptions::default()).unwrap();
+            let christmas_date = date(25, 12, lit_year);
+            let (christmas, epiphany): (Vec<Event>, Vec<Event>) =
+                christmas_and_epiphany_events(lit_year)
+                    .unwrap()
+                    .into_iter()
+                    .partition(|ev| ev.label.starts_with("christmas"));
+
+            // "christmas" on 25 December and "christmas + 1" on the Sunday after it,
+            // never dropped, even when Christmas is itself a Sunday.
+            let labels: Vec<&str> = christmas.iter().map(|ev| ev.label.as_str()).collect();
+            assert_eq!(labels, ["christmas", "christmas + 1"], "{}", lit_year);
+            assert_eq!(christmas[0].date, christmas_date);
+            assert_eq!(
+                christmas[1].date,
+                first_sunday_on_or_after(christmas_date + Duration::days(1))
+            );
+
+            // Seven Epiphany Sundays from 2 to 8 January, one week after
+            // "christmas + 1" and before the first pre-Easter Sunday.
+            assert_eq!(epiphany.len(), 7, "{}", lit_year);
+            assert_eq!(epiphany[0].label, "epiphany");
+            assert_eq!(epiphany[0].date, christmas[1].date + Duration::days(7));
+            assert!(
+                (date(2, 1, lit_year + 1)..=date(8, 1, lit_year + 1)).contains(&epiphany[0].date),
+                "{}",
+                lit_year
+            );
+            assert!(epiphany[0].date < anchors.easter - Duration::days(7 * 9));
+        }
+    }
+
+    #[test]
+    fn fixed_feasts_never_tie_in_strict_mode() {
+        let options = CalendarOptions {
+            strict: true,
+            include_fixed_feasts: true,
+            ..Default::default()
+        };
+        for lit_year in 1900..=2200 {
+            if let Err(e) = generate_events(lit_year, &options) {
+                panic!("{}: {}", lit_year, e);
+            }
+        }
+    }
+
+    #[test]
+    fn candlemas_displaces_the_transfiguration() {
+        let options = CalendarOptions {
+            include_fixed_feasts: true,
+            ..Default::default()
+        };
+        // Easter 2020 is on 12 April, so the Transfiguration would be on 2 February.
+        let events = generate_events(2019, &options).unwrap();
+        let ev = events
+            .iter()
+            .find(|ev| ev.date == date(2, 2, 2020))
+            .unwrap();
+        assert_eq!(ev.label, "candlemas");
+        assert!(events.iter().all(|ev| ev.label != "transfiguration"));
+    }
+
+    #[test]
+    fn events_stay_inside_their_liturgical_year() {
+        let options = CalendarOptions {
+            include_vigils: true,
+            include_corpus_christi: true,
+            include_movable_feasts: true,
+            include_fixed_feasts: true,
+            ..Default::default()
+        };
+        for lit_year in 1900..=2200 {
+            let start = first_sunday_of_advent(lit_year).unwrap();
+            let end = first_sunday_of_advent(lit_year + 1).unwrap();
+            for ev in generate_events(lit_year, &options).unwrap() {
+                assert!(
+                    start <= ev.date && ev.date < end,
+                    "{:?} of {} lies outside [{}, {})",
+                    ev.label,
+                    lit_year,
+                    start,
+                    end
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_day_of_the_year_has_a_previous_event() {
+        for lit_year in [1990, 2011, 2024, 2025, 2038, 2100] {
+            let events = generate_events(lit_year, &CalendarOptions::default()).unwrap();
+            let mut day = first_sunday_of_advent(lit_year).unwrap();
+            let end = first_sunday_of_advent(lit_year + 1).unwrap();
+            while day < end {
+                assert_eq!(compute_liturgical_year(day), lit_year);
+                let ev = previous_event(&events, day);
+                assert!(ev.is_some_and(|ev| ev.date <= day), "{}", day);
+                day += Duration::days(1);
+            }
+        }
+    }
+
+    #[test]
+    fn advent_i_opens_the_new_liturgical_year() {
+        // Advent I 2024 is on 24 November; the Saturday before still belongs to 2023.
+        assert_eq!(first_sunday_of_advent(2024).unwrap(), date(24, 11, 2024));
+        assert_eq!(compute_liturgical_year(date(24, 11, 2024)), 2024);
+        assert_eq!(compute_liturgical_year(date(23, 11, 2024)), 2023);
+        // The latest Advent I, 27 November, and the earliest, 21 November.
+        assert_eq!(first_sunday_of_advent(2022).unwrap(), date(27, 11, 2022));
+        assert_eq!(compute_liturgical_year(date(27, 11, 2022)), 2022);
+        assert_eq!(compute_liturgical_year(date(26, 11, 2022)), 2021);
+        assert_eq!(first_sunday_of_advent(2021).unwrap(), date(21, 11, 2021));
+        assert_eq!(compute_liturgical_year(date(21, 11, 2021)), 2021);
+        assert_eq!(compute_liturgical_year(date(20, 11, 2021)), 2020);
+        // January belongs to the year that began the previous November.
+        assert_eq!(compute_liturgical_year(date(1, 1, 2025)), 2024);
+        assert_eq!(compute_liturgical_year(date(31, 12, 2024)), 2024);
+    }
+
+    #[test]
+    fn season_index_follows_the_label_between_feasts() {
+        let options = CalendarOptions {
+            include_fixed_feasts: true,
+            include_movable_feasts: true,
+            ..Default::default()
+        };
+        let events = generate_events(2025, &options).unwrap();
+        let records = event_records(2025, events, &Palette::default(), &options);
+        let index = |label: &str| {
+            records
+                .iter()
+                .find(|record| record.label == label)
+                .unwrap_or_else(|| panic!("no {:?}", label))
+                .season_index
+        };
+        // Epiphany Day (6 January 2026) falls between "epiphany" and "epiphany + 1".
+        assert_eq!(index("epiphany"), 0);
+        assert_eq!(index("epiphany day"), 0);
+        assert_eq!(index("epiphany + 1"), 1);
+        assert_eq!(index("easter - 9"), 0);
+        assert_eq!(index("ash wednesday"), 2);
+        assert_eq!(index("easter - 1"), 8);
+        // Michaelmas and Reformation Day fall on weekdays, and All Saints displaces
+        // "trinity + 22".
+        assert_eq!(index("michaelmas"), 17);
+        assert_eq!(index("trinity + 18"), 18);
+        assert_eq!(index("all saints"), 22);
+        assert_eq!(index("trinity + 23"), 23);
+    }
+
+    #[test]
+    fn event_records_carry_civil_and_liturgical_dates() {
+        let options = CalendarOptions::default();
+        let events = generate_events(2024, &options).unwrap();
+        let records = event_records(2024, events, &Palette::default(), &options);
+        let record = records
+            .iter()
+            .find(|record| record.label == "advent + 2")
+            .unwrap();
+        let json = serde_json::to_value(record).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "id": "2024-2025/advent%2B2",
+                "date": "2024-12-08",
+                "civil_date": "08/12/2024",
+                "label": "advent + 2",
+                "color": "purple",
+                "hex": Palette::default().hex(AltarColor::Purple),
+                "season": "advent",
+                "season_index": 2,
+                "week": 3,
+                "weeks_from_easter": -19,
+                "anchor": "advent",
+                "offset": 2,
+                "is_principal": false,
+                "dating": "movable",
+            })
+        );
+        let offset = |label: &str| {
+            let record = records.iter().find(|record| record.label == label).unwrap();
+            let json = serde_json::to_value(record).unwrap();
+            (json["anchor"].clone(), json["offset"].clone())
+        };
+        // Christmas 2024 is a Wednesday, in the fifth week after Advent I.
+        assert_eq!(offset("christmas"), ("advent".into(), 4.into()));
+        assert_eq!(offset("christmas + 1"), ("advent".into(), 5.into()));
+        assert_eq!(offset("epiphany + 1"), ("advent".into(), 7.into()));
+        assert_eq!(offset("easter - 3"), ("easter".into(), (-3).into()));
+        assert_eq!(offset("trinity"), ("easter".into(), 8.into()));
+    }
+
+    #[test]
+    fn season_bands_cover_the_year_without_gaps() {
+        let bands = season_bands(2024, &CalendarOptions::default(), &Palette::default()).unwrap();
+        let seasons: Vec<Season> = bands.iter().map(|band| band.season).collect();
+        assert_eq!(seasons, Season::ALL);
+        assert_eq!(bands[0].start, date(24, 11, 2024));
+        assert_eq!(bands[6].end, date(22, 11, 2025));
+        for pair in bands.windows(2) {
+            assert_eq!(pair[0].end + Duration::days(1), pair[1].start);
+        }
+
+        let json = serde_json::to_value(&bands[1]).unwrap();
+        assert_eq!(json["season"], "christmas");
+        assert_eq!(json["start"], "2024-12-25");
+        assert_eq!(json["end"], "2025-01-04");
+        let labels: Vec<&str> = json["events"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| event["label"].as_str().unwrap())
+            .collect();
+        assert_eq!(labels, ["christmas", "christmas + 1"]);
+        assert_eq!(json["events"][1]["date"], "2024-12-29");
+        assert_eq!(json["events"][1]["color"], "white");
+    }
+
+    #[test]
+    fn palette_overrides_and_normalizes_hex_values() {
+        let path = temp_file("palette.toml", "purple = \"#5B2A86\"\nwhite = \"#fff\"\n");
+        let palette = Palette::load(&path).unwrap();
+        assert_eq!(palette.hex(AltarColor::Purple), "#5b2a86");
+        assert_eq!(palette.hex(AltarColor::White), "#ffffff");
+        assert_eq!(
+            palette.hex(AltarColor::Green),
+            AltarColor::Green.builtin_hex()
+        );
+        assert_eq!(
+            palette.missing([
+                AltarColor::Red,
+                AltarColor::Purple,
+                AltarColor::Green,
+                AltarColor::Red
+            ]),
+            [AltarColor::Green, AltarColor::Red]
+        );
+
+        let path = temp_file("bad-palette.toml", "purple = \"#5b2a8\"\n");
+        assert!(Palette::load(&path)
+            .unwrap_err()
+            .contains("Invalid hex value"));
+        let path = temp_file("unknown-palette.toml", "violet = \"#5b2a86\"\n");
+        assert!(Palette::load(&path)
+            .unwrap_err()
+            .contains("Unknown altar color"));
+    }
+
+    #[test]
+    fn civil_year_joins_two_liturgical_years() {
+        let events = civil_year_events(2025, &CalendarOptions::default()).unwrap();
+        // 52 Sundays (1 January 2025 is a Wednesday) and Christmas, a Thursday.
+        assert_eq!(events.len(), 53);
+        assert!(events.iter().all(|ev| ev.date.year() == 2025));
+        assert_eq!(events[0].label, "epiphany");
+        assert_eq!(events[0].date, date(5, 1, 2025));
+        let last = events.last().unwrap();
+        assert_eq!(
+            (last.label.as_str(), last.date),
+            ("christmas + 1", date(28, 12, 2025))
+        );
+        assert!(events.windows(2).all(|pair| pair[0].date < pair[1].date));
+    }
+
+    #[test]
+    fn epiphany_sundays_lie_between_christmas_and_pre_easter() {
+        for lit_year in 1583..=2600 {
+            let events = generate_events(lit_year, &CalendarOptions::default()).unwrap();
+            let date_of = |label: &str| {
+                events
+                    .iter()
+                    .find(|ev| ev.label == label)
+                    .unwrap_or_else(|| panic!("{} has no {:?}", lit_year, label))
+                    .date
+            };
+            let (christmas_1, pre_easter) = (date_of("christmas + 1"), date_of("easter - 9"));
+            assert_eq!(date_of("epiphany"), christmas_1 + Duration::days(7));
+            for ev in events.iter().filter(|ev| ev.label.starts_with("epiphany")) {
+                assert!(
+                    christmas_1 < ev.date && ev.date < pre_easter,
+                    "{:?} of {} on {}",
+                    ev.label,
+                    lit_year,
+                    ev.date
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn red_dates_are_pentecost_and_the_red_trinity_sunday() {
+        let options = CalendarOptions::default();
+        assert_eq!(
+            dates_with_color(2024, &options, AltarColor::Red).unwrap(),
+            [date(8, 6, 2025), date(20, 7, 2025)]
+        );
+        let options = CalendarOptions {
+            include_movable_feasts: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            dates_with_color(2024, &options, AltarColor::Red).unwrap(),
+            [date(18, 4, 2025), date(8, 6, 2025), date(20, 7, 2025)]
+        );
+    }
+
+    #[test]
+    fn readings_diff_sorts_keys_into_three_lists() {
+        let defaults = default_readings();
+        let key = |label: &str, set| (label.to_string(), set);
+        let mut user: CustomReadings = HashMap::new();
+        // One unchanged entry, one override and one addition.
+        user.insert(key("advent", 1), defaults[&key("advent", 1)].clone());
+        let mut changed = defaults[&key("advent", 2)].clone();
+        changed.2 = "Joh 1:1-14".to_string();
+        user.insert(key("advent", 2), changed);
+        let readings = (
+            "a".to_string(),
+            "b".to_string(),
+            "c".to_string(),
+            "d".to_string(),
+        );
+        user.insert(key("harvest festival", 1), readings);
+
+        let diff = diff_readings(&user, &defaults);
+        assert_eq!(diff.only_user, [key("harvest festival", 1)]);
+        assert_eq!(diff.changed, [key("advent", 2)]);
+        assert_eq!(diff.only_default.len(), defaults.len() - 2);
+        assert!(!diff.only_default.contains(&key("advent", 1)));
+        assert!(diff.only_default.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn identifiers_are_stable_and_url_safe() {
+        assert_eq!(liturgical_year_id(2024), "2024-2025");
+        assert_eq!(event_id(2024, "trinity + 5"), "2024-2025/trinity%2B5");
+        assert_eq!(event_id(2024, "easter - 9"), "2024-2025/easter-9");
+        assert_eq!(event_id(2024, "all saints"), "2024-2025/all%20saints");
+        assert_eq!(encode_path_segment("a/b?c"), "a%2Fb%3Fc");
+        let encoded = encode_path_segment("Ostersonntag + 1 ü");
+        assert!(encoded
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"-._~%".contains(&byte)));
+        assert_eq!(encoded, "Ostersonntag%2B1%20%C3%BC");
+    }
+
+    #[test]
+    fn vigils_fall_on_the_saturdays_before_easter_and_pentecost() {
+        let options = CalendarOptions {
+            include_vigils: true,
+            ..Default::default()
+        };
+        let events = generate_events(2024, &options).unwrap();
+        let vigil = |label: &str| {
+            let ev = events.iter().find(|ev| ev.label == label).unwrap();
+            (ev.date, ev.date.weekday(), ev.altar_color)
+        };
+        // Easter 2025 is on 20 April, Pentecost on 8 June.
+        assert_eq!(
+            vigil("easter vigil"),
+            (date(19, 4, 2025), Weekday::Sat, AltarColor::White)
+        );
+        assert_eq!(
+            vigil("pentecost vigil"),
+            (date(7, 6, 2025), Weekday::Sat, AltarColor::Red)
+        );
+        let without = generate_events(2024, &CalendarOptions::default()).unwrap();
+        assert_eq!(without.len() + 2, events.len());
+    }
+
+    #[test]
+    fn strict_mode_rejects_conflicting_series() {
+        let path = temp_file(
+            "conflict.toml",
+            "[[series]]\nname = \"advent\"\nanchor = \"advent\"\nuntil = \"christmas\"\n\
+             colors = [\"purple\"]\npriority = 1\n\n\
+             [[series]]\nname = \"rorate\"\nanchor = \"advent\"\ncount = 2\n\
+             colors = [\"purple\"]\npriority = 1\n",
+        );
+        let series = load_config(&path).unwrap().series;
+        let options = CalendarOptions {
+            series: series.clone(),
+            strict: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_events(2024, &options).unwrap_err(),
+            CalendarError::Collision {
+                date: date(24, 11, 2024),
+                existing: "advent".to_string(),
+                incoming: "rorate".to_string(),
+                priority: 1,
+            }
+        );
+        // Without --strict the alphabetically first label keeps the date.
+        let options = CalendarOptions {
+            series,
+            ..Default::default()
+        };
+        let events = generate_events(2024, &options).unwrap();
+        assert_eq!(events[0].label, "advent");
+        assert_eq!(events[1].label, "advent + 1");
+
+        let path = temp_file(
+            "undefined-color.toml",
+            "[[color_override]]\nlabel = \"advent\"\ncolor = \"blue\"\n",
+        );
+        assert!(load_config(&path)
+            .unwrap_err()
+            .contains("Unknown altar color"));
+    }
+
+    #[test]
+    fn reading_keys_need_a_known_label_and_set() {
+        let options = CalendarOptions::default();
+        let cycle = SetCycle::default();
+        assert!(is_valid_reading_key(
+            "trinity + 5",
+            2,
+            2024,
+            &options,
+            cycle
+        ));
+        assert!(!is_valid_reading_key(
+            "trinity + 5",
+            0,
+            2024,
+            &options,
+            cycle
+        ));
+        assert!(!is_valid_reading_key(
+            "trinity + 5",
+            4,
+            2024,
+            &options,
+            cycle
+        ));
+        assert!(!is_valid_reading_key(
+            "trinity + 40",
+            1,
+            2024,
+            &options,
+            cycle
+        ));
+        // Optional events are only valid when they are generated.
+        assert!(!is_valid_reading_key(
+            "easter vigil",
+            1,
+            2024,
+            &options,
+            cycle
+        ));
+        let options = CalendarOptions {
+            include_vigils: true,
+            ..Default::default()
+        };
+        assert!(is_valid_reading_key(
+            "easter vigil",
+            1,
+            2024,
+            &options,
+            cycle
+        ));
+    }
+
+    #[test]
+    fn sunday_counts_follow_the_date_of_easter() {
+        let options = CalendarOptions::default();
+        // Easter on 23 March 2008, one of the earliest, and on 25 April 2038, the latest.
+        let (early, late) = (2007, 2037);
+        assert_eq!(sunday_count(early, &options).unwrap(), 52);
+        assert_eq!(sunday_count(late, &options).unwrap(), 52);
+        let count = |year, season| season_sunday_count(year, season, &options).unwrap();
+        assert_eq!(
+            (
+                count(early, Season::Epiphany),
+                count(early, Season::Trinity)
+            ),
+            (2, 27)
+        );
+        assert_eq!(
+            (count(late, Season::Epiphany), count(late, Season::Trinity)),
+            (7, 22)
+        );
+        // Every Sunday of a year has an event, so a 53-week year has 53.
+        assert_eq!(sunday_count(2010, &options).unwrap(), 53);
+        for lit_year in 1900..=2200 {
+            let weeks = (first_sunday_of_advent(lit_year + 1).unwrap()
+                - first_sunday_of_advent(lit_year).unwrap())
+            .num_days()
+                / 7;
+            assert_eq!(sunday_count(lit_year, &options).unwrap() as i64, weeks);
+        }
+    }
+
+    #[test]
+    fn color_override_turns_a_green_sunday_red() {
+        let sunday = date(13, 7, 2025);
+        let plain = generate_events(2024, &CalendarOptions::default()).unwrap();
+        let event = previous_event(&plain, sunday).unwrap();
+        assert_eq!((event.date, event.label.as_str()), (sunday, "trinity + 4"));
+        assert_eq!(event.altar_color, AltarColor::Green);
+
+        let path = temp_file(
+            "dedication.toml",
+            "[[color_override]]\ndate = \"13/07/2025\"\ncolor = \"red\"\n",
+        );
+        let config = load_config(&path).unwrap();
+        let options = CalendarOptions {
+            color_overrides: config.color_overrides,
+            ..Default::default()
+        };
+        let events = generate_events(2024, &options).unwrap();
+        let event = previous_event(&events, sunday).unwrap();
+        assert_eq!(event.label, "trinity + 4");
+        assert_eq!(event.altar_color, AltarColor::Red);
+        // The previous Sunday keeps its color.
+        let before = previous_event(&events, date(6, 7, 2025)).unwrap();
+        assert_eq!(before.altar_color, AltarColor::Green);
+    }
+
+    #[test]
+    fn readings_file_with_bom_and_crlf_loads() {
+        let path = temp_file(
+            "windows-readings.toml",
+            "\u{feff}[[reading]]\r\nlabel = \"epiphany + 5\"\r\nset = 1\r\n\
+             old_testament = \"Jer 17:5-10\"\r\nlection = \"Col 3:12-17\"\r\n\
+             gospel = \"Mat 13:31-35\"\r\npreaching = \"Mat 13:24-30\"\r\n",
+        );
+        let readings = load_readings(&path).unwrap();
+        assert_eq!(
+            readings[&("epiphany + 5".to_string(), 1)],
+            (
+                "Jer 17:5-10".to_string(),
+                "Col 3:12-17".to_string(),
+                "Mat 13:31-35".to_string(),
+                "Mat 13:24-30".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn principal_feasts_are_flagged() {
+        let events = generate_events(2024, &CalendarOptions::default()).unwrap();
+        let principal = |label: &str| {
+            events
+                .iter()
+                .find(|ev| ev.label == label)
+                .unwrap_or_else(|| panic!("no {}", label))
+                .is_principal()
+        };
+        assert!(principal("christmas"));
+        assert!(principal("easter"));
+        assert!(principal("pentecost"));
+        assert!(!principal("trinity + 4"));
+        assert!(!principal("advent + 1"));
+    }
+
+    #[test]
+    fn christmas_octave_window_lets_the_fixed_feast_win() {
+        // A fifth Advent Sunday reaches 25/12/2022, a Sunday, and outranks Christmas.
+        let series = "[[series]]\nname = \"advent\"\nanchor = \"advent\"\ncount = 5\n\
+                      colors = [\"purple\"]\npriority = 5\n\n\
+                      [[series]]\nname = \"christmas\"\nanchor = \"christmas\"\ncount = 2\n\
+                      colors = [\"white\"]\npriority = 2\n";
+        let christmas_day = |config: &str| {
+            let config = load_config(&temp_file("octave.toml", config)).unwrap();
+            let options = CalendarOptions {
+                series: config.series,
+                fixed_feast_windows: config.fixed_feast_windows,
+                strict: true,
+                ..Default::default()
+            };
+            let events = generate_events(2022, &options).unwrap();
+            previous_event(&events, date(25, 12, 2022))
+                .unwrap()
+                .label
+                .clone()
+        };
+        assert_eq!(christmas_day(series), "advent + 4");
+        let windowed = format!(
+            "[[fixed_feast_window]]\nstart = \"25/12\"\nend = \"01/01\"\n\n{}",
+            series
+        );
+        assert_eq!(christmas_day(&windowed), "christmas");
+    }
+
+    #[test]
+    fn corpus_christi_is_the_thursday_after_trinity() {
+        let options = CalendarOptions {
+            include_corpus_christi: true,
+            ..Default::default()
+        };
+        let events = generate_events(2024, &options).unwrap();
+        let ev = events
+            .iter()
+            .find(|ev| ev.label == "corpus christi")
+            .unwrap();
+        assert_eq!(ev.date, date(20, 4, 2025) + Duration::days(60));
+        assert_eq!(ev.date, date(19, 6, 2025));
+        assert_eq!(ev.date.weekday(), Weekday::Thu);
+        assert_eq!(ev.altar_color, AltarColor::White);
+        let plain = generate_events(2024, &CalendarOptions::default()).unwrap();
+        assert!(plain.iter().all(|ev| ev.label != "corpus christi"));
+    }
+
+    #[test]
+    fn easter_is_movable_and_christmas_fixed() {
+        let options = CalendarOptions {
+            include_fixed_feasts: true,
+            ..Default::default()
+        };
+        let events = generate_events(2024, &options).unwrap();
+        let dating = |label: &str| events.iter().find(|ev| ev.label == label).unwrap().dating;
+        assert_eq!(dating("easter"), Dating::Movable);
+        assert_eq!(dating("advent"), Dating::Movable);
+        assert_eq!(dating("christmas"), Dating::Fixed);
+        assert_eq!(dating("christmas + 1"), Dating::Movable);
+        assert_eq!(dating("epiphany day"), Dating::Fixed);
+        assert_eq!(dating("transfiguration"), Dating::Movable);
+    }
+
+    #[test]
+    fn orthodox_pascha_matches_known_dates() {
+        for (year, pascha) in [
+            (1900, date(22, 4, 1900)),
+            (1950, date(9, 4, 1950)),
+            (2000, date(30, 4, 2000)),
+            (2021, date(2, 5, 2021)),
+            (2024, date(5, 5, 2024)),
+            (2025, date(20, 4, 2025)),
+            // From 2100 the Julian calendar lags by 14 days instead of 13.
+            (2100, date(2, 5, 2100)),
+        ] {
+            assert_eq!(compute_easter_julian(year).unwrap(), pascha, "{}", year);
+        }
+        let options = CalendarOptions {
+            easter_reckoning: EasterReckoning::Orthodox,
+            ..Default::default()
+        };
+        let events = generate_events(2023, &options).unwrap();
+        let easter = events.iter().find(|ev| ev.label == "easter").unwrap();
+        assert_eq!(easter.date, date(5, 5, 2024));
+    }
+
+    #[test]
+    fn advent_has_four_or_five_sundays_before_christmas() {
+        for (lit_year, advent_i, sundays) in [
+            (2021, date(21, 11, 2021), 5),
+            (2022, date(27, 11, 2022), 4),
+            (2024, date(24, 11, 2024), 5),
+        ] {
+            let events = generate_events(lit_year, &CalendarOptions::default()).unwrap();
+            let advent: Vec<&Event> = events
+                .iter()
+                .filter(|ev| ev.label.starts_with("advent"))
+                .collect();
+            assert_eq!(advent[0].date, advent_i, "{}", lit_year);
+            assert_eq!(advent.len(), sundays, "{}", lit_year);
+            let christmas = date(25, 12, lit_year);
+            assert!(advent.iter().all(|ev| ev.date < christmas), "{}", lit_year);
+            assert!(
+                advent.last().unwrap().date + Duration::days(7) >= christmas,
+                "{}",
+                lit_year
+            );
+        }
+    }
+
+    #[test]
+    fn gospel_set_rotates_to_the_next_set() {
+        assert_eq!([1, 2, 3].map(gospel_set), [2, 3, 1]);
+        let four = SetCycle {
+            anchor: DEFAULT_SET_ANCHOR,
+            length: 4,
+        };
+        assert_eq!([1, 2, 3, 4].map(|set| four.gospel_set(set)), [2, 3, 4, 1]);
+        let one = SetCycle {
+            anchor: DEFAULT_SET_ANCHOR,
+            length: 1,
+        };
+        assert_eq!(one.gospel_set(1), 1);
+        let readings = DefaultReadingProvider::default()
+            .readings("advent", 3)
+            .unwrap();
+        assert_eq!(readings.gospel, "Gospel reading for advent (Set 1)");
+    }
+
+    #[test]
+    fn trinity_series_ends_before_the_next_advent() {
+        // Liturgical years with early (2008, 2024), middling and late (2011, 2038) Easters.
+        for lit_year in [2007, 2010, 2015, 2023, 2024, 2037] {
+            let events = generate_events(lit_year, &CalendarOptions::default()).unwrap();
+            let trinity: Vec<&Event> = events
+                .iter()
+                .filter(|ev| ev.label.starts_with("trinity"))
+                .collect();
+            let next_advent = first_sunday_of_advent(lit_year + 1).unwrap();
+            let last = trinity.last().unwrap();
+            assert!(last.date < next_advent, "{}", lit_year);
+            assert_eq!(last.date + Duration::days(7), next_advent, "{}", lit_year);
+            for (i, ev) in trinity.iter().enumerate().skip(1) {
+                assert_eq!(ev.label, format!("trinity + {}", i), "{}", lit_year);
+            }
+        }
+    }
+
+    #[test]
+    fn christmas_sundays_for_each_weekday_of_christmas() {
+        for (lit_year, weekday, christmas_1, epiphany) in [
+            (2022, Weekday::Sun, date(1, 1, 2023), date(8, 1, 2023)),
+            (2023, Weekday::Mon, date(31, 12, 2023), date(7, 1, 2024)),
+            (2018, Weekday::Tue, date(30, 12, 2018), date(6, 1, 2019)),
+            (2024, Weekday::Wed, date(29, 12, 2024), date(5, 1, 2025)),
+            (2025, Weekday::Thu, date(28, 12, 2025), date(4, 1, 2026)),
+            (2020, Weekday::Fri, date(27, 12, 2020), date(3, 1, 2021)),
+            (2021, Weekday::Sat, date(26, 12, 2021), date(2, 1, 2022)),
+        ] {
+            let christmas = date(25, 12, lit_year);
+            assert_eq!(christmas.weekday(), weekday);
+            let events = christmas_and_epiphany_events(lit_year).unwrap();
+            assert_eq!(events.len(), 9, "{}", lit_year);
+            let date_of = |label: &str| events.iter().find(|ev| ev.label == label).unwrap().date;
+            assert_eq!(date_of("christmas"), christmas, "{}", lit_year);
+            assert_eq!(date_of("christmas + 1"), christmas_1, "{}", lit_year);
+            assert_eq!(date_of("epiphany"), epiphany, "{}", lit_year);
+            assert_eq!(date_of("epiphany + 6"), epiphany + Duration::days(42));
+        }
+    }
+
+    #[test]
+    fn set_cycles_of_one_two_and_three_years() {
+        let sets = |length: i32| -> Vec<i32> {
+            let cycle = SetCycle {
+                anchor: DEFAULT_SET_ANCHOR,
+                length,
+            };
+            (2020..=2029).map(|lit_year| cycle.set(lit_year)).collect()
+        };
+        assert_eq!(sets(1), [1; 10]);
+        assert_eq!(sets(2), [1, 2, 1, 2, 1, 2, 1, 2, 1, 2]);
+        assert_eq!(sets(3), [3, 1, 2, 3, 1, 2, 3, 1, 2, 3]);
+        let three: Vec<i32> = (2020..=2029).map(compute_set).collect();
+        assert_eq!(three, sets(3));
+    }
+
+    #[test]
+    fn transfiguration_replaces_the_last_epiphany_sunday() {
+        let options = CalendarOptions {
+            include_fixed_feasts: true,
+            ..Default::default()
+        };
+        for (lit_year, transfiguration) in [(2023, date(21, 1, 2024)), (2024, date(9, 2, 2025))] {
+            let events = generate_events(lit_year, &options).unwrap();
+            let ev = events
+                .iter()
+                .find(|ev| ev.label == "transfiguration")
+                .unwrap();
+            assert_eq!(ev.date, transfiguration, "{}", lit_year);
+            assert_eq!(ev.altar_color, AltarColor::White);
+            let next = events.iter().find(|ev| ev.date > transfiguration).unwrap();
+            assert_eq!(next.label, "easter - 9", "{}", lit_year);
+
+            let plain = generate_events(lit_year, &CalendarOptions::default()).unwrap();
+            let displaced = previous_event(&plain, transfiguration).unwrap();
+            assert_eq!(displaced.date, transfiguration);
+            assert!(displaced.label.starts_with("epiphany"), "{}", lit_year);
+            assert_eq!(displaced.altar_color, AltarColor::Green);
+        }
+    }
+
+    #[test]
+    fn equal_priority_collisions_go_to_the_first_label_in_any_order() {
+        let advent = "[[series]]\nname = \"advent\"\nanchor = \"advent\"\nuntil = \"christmas\"\n\
+                      colors = [\"purple\"]\npriority = 1\n\n";
+        let rorate = "[[series]]\nname = \"rorate\"\nanchor = \"advent\"\ncount = 2\n\
+                      colors = [\"white\"]\npriority = 1\n\n";
+        for config in [
+            format!("{}{}", advent, rorate),
+            format!("{}{}", rorate, advent),
+        ] {
+            let series = load_config(&temp_file("tie.toml", &config)).unwrap().series;
+            let options = CalendarOptions {
+                series,
+                ..Default::default()
+            };
+            let events = generate_events(2024, &options).unwrap();
+            assert_eq!(events[0].label, "advent", "{}", config);
+            assert_eq!(events[0].altar_color, AltarColor::Purple);
+            assert!(events.iter().all(|ev| !ev.label.starts_with("rorate")));
+        }
+
+        let event = |label: &str, priority| Event {
+            label: label.to_string(),
+            date: date(24, 11, 2024),
+            altar_color: AltarColor::Purple,
+            priority,
+            dating: Dating::Movable,
+        };
+        assert!(event("advent", 1).outranks(&event("rorate", 1)));
+        assert!(!event("rorate", 1).outranks(&event("advent", 1)));
+        assert!(event("rorate", 2).outranks(&event("advent", 1)));
+    }
+
+    #[test]
+    fn ascension_is_a_thursday_ten_days_before_pentecost() {
+        let options = CalendarOptions {
+            include_movable_feasts: true,
+            ..Default::default()
+        };
+        for lit_year in 1900..=2200 {
+            let events = generate_events(lit_year, &options).unwrap();
+            let find = |label: &str| events.iter().find(|ev| ev.label == label).unwrap();
+            let (ascension, pentecost) = (find("ascension"), find("pentecost"));
+            assert_eq!(ascension.date.weekday(), Weekday::Thu, "{}", lit_year);
+            assert_eq!(ascension.date + Duration::days(10), pentecost.date);
+            assert_eq!(ascension.date, ascension_day(lit_year + 1).unwrap());
+            assert_eq!(ascension.altar_color, AltarColor::White);
+            // The Sunday after Ascension keeps its own label and color.
+            let sunday_after = find("easter + 6");
+            assert_eq!(sunday_after.date, ascension.date + Duration::days(3));
+            assert_eq!(sunday_after.altar_color, AltarColor::White);
+            assert_eq!(pentecost.altar_color, AltarColor::Red);
+        }
+    }
+
+    #[test]
+    fn generated_colors_pass_validation_and_swaps_are_flagged() {
+        let options = CalendarOptions {
+            include_vigils: true,
+            include_corpus_christi: true,
+            include_movable_feasts: true,
+            include_fixed_feasts: true,
+            ..Default::default()
+        };
+        for lit_year in 1900..=2200 {
+            let events = generate_events(lit_year, &options).unwrap();
+            assert_eq!(validate_events(&events), Ok(()), "{}", lit_year);
+        }
+
+        let mut events = generate_events(2024, &CalendarOptions::default()).unwrap();
+        events[1].altar_color = AltarColor::Green;
+        let pentecost = events
+            .iter_mut()
+            .find(|ev| ev.label == "pentecost")
+            .unwrap();
+        pentecost.altar_color = AltarColor::White;
+        assert_eq!(
+            validate_events(&events).unwrap_err(),
+            "\"advent + 1\" on 01/12/2024 is green but should be purple\n\
+             \"pentecost\" on 08/06/2025 is white but should be red"
+        );
+    }
+
+    #[test]
+    fn holy_week_displaces_the_annunciation() {
+        let options = CalendarOptions {
+            include_fixed_feasts: true,
+            include_movable_feasts: true,
+            ..Default::default()
+        };
+        let annunciation = |lit_year| {
+            generate_events(lit_year, &options)
+                .unwrap()
+                .into_iter()
+                .find(|ev| ev.label == "annunciation")
+                .map(|ev| ev.date)
+        };
+        // 25/03/2024 is Holy Monday (Easter on 31 March) and 25/03/2029 Palm Sunday.
+        assert_eq!(annunciation(2023), None);
+        assert_eq!(annunciation(2028), None);
+        // 25/03/2025 is a Tuesday in Lent, and 25/03/2016 Good Friday.
+        assert_eq!(annunciation(2024), Some(date(25, 3, 2025)));
+        let events = generate_events(2015, &options).unwrap();
+        let good_friday = previous_event(&events, date(25, 3, 2016)).unwrap();
+        assert_eq!(good_friday.label, "good friday");
+        assert!(events.iter().all(|ev| ev.label != "annunciation"));
+    }
+
+    #[test]
+    fn range_errors_name_the_year_that_overflowed() {
+        let options = CalendarOptions::default();
+        // NaiveDate ends in 262142, so 262141 is the last liturgical year that fits.
+        let events = generate_events(262141, &options).unwrap();
+        assert_eq!(events[0].label, "advent");
+        assert_eq!(events.last().unwrap().date.year(), 262142);
+        let error = generate_events(262142, &options).unwrap_err();
+        assert_eq!(
+            error,
+            CalendarError::FollowingYearOutOfRange { lit_year: 262142 }
+        );
+        assert_eq!(
+            error.to_string(),
+            "liturgical year 262142 ends in 262143, which is outside the supported range"
+        );
+        let error = generate_events(300000, &options).unwrap_err();
+        assert_eq!(error, CalendarError::YearOutOfRange(300000));
+        assert_eq!(
+            error.to_string(),
+            "year 300000 is outside the supported range"
+        );
+        assert_eq!(
+            generate_events(i32::MAX, &options).unwrap_err(),
+            CalendarError::YearOutOfRange(i32::MAX)
+        );
+    }
+
+    #[test]
+    fn extreme_set_anchors_do_not_overflow() {
+        let cycle = |anchor| SetCycle {
+            anchor,
+            length: SET_COUNT,
+        };
+        // 2024 - i32::MIN = 2^31 + 2024 leaves 1 when divided by 3, so Set II.
+        assert_eq!(cycle(i32::MIN).set(2024), 2);
+        assert_eq!(cycle(i32::MAX).set(i32::MIN), 1);
+        assert_eq!(cycle(i32::MAX).set(i32::MAX), 1);
+        assert_eq!(cycle(i32::MIN).set(i32::MIN), 1);
+        assert_eq!(cycle(DEFAULT_SET_ANCHOR).set(2025), 2);
+    }
+}
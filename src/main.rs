@@ -1,6 +1,25 @@
-use chrono::{Datelike, Duration, NaiveDate, Weekday};
-use clap::Parser;
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+use chrono_tz::Tz;
+use clap::{Parser, Subcommand, ValueEnum};
+use liturgical_calendar::{
+    civil_year_events, color_histogram, compute_easter, compute_easter_julian,
+    compute_liturgical_year, date_for_label, dates_with_color, default_readings, diff_readings,
+    encode_path_segment, event_records, events_in_range, first_sunday_of_advent,
+    first_sunday_on_or_after, generate_events, is_event_date, is_valid_reading_key, known_label,
+    load_config, load_readings, next_event_from, normalize_text, previous_event, read_text_file,
+    season_bands, season_for_date, season_sunday_count, sunday_count, traditional_name,
+    validate_events, year_anchors, AltarColor, CalendarCache, CalendarConfig, CalendarError,
+    CalendarOptions, CustomReadings, DefaultReadingProvider, EasterReckoning, Event, EventRecord,
+    HashMapReadingProvider, Palette, ReadingProvider, Readings, ReadingsDiff, Season, SetCycle,
+    WeekOffset, YearAnchors, DEFAULT_SET_ANCHOR, SET_COUNT,
+};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use unicode_width::UnicodeWidthStr;
 
 /// A program to compute the liturgical pericope and Bible readings for a given date.
 /// It supports both default (placeholder) readings and custom Bible readings
@@ -14,355 +33,2228 @@ use std::collections::HashMap;
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// Date in dd/mm/yyyy format, e.g. "08/02/2025"
-    date: String,
+    /// Dates in dd/mm/yyyy or yyyy-mm-dd format, e.g. "08/02/2025", or one of `today`,
+    /// `tomorrow`, `yesterday` and `next-sunday`. Each is looked up in turn; defaults
+    /// to today (see `--today`).
+    date: Vec<String>,
+
+    /// Look up every date listed in the file at PATH, or on stdin for `-`, one date
+    /// per line; blank lines and lines starting with `#` are skipped.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["date", "year"])]
+    dates_file: Option<String>,
+
+    /// Print the first event after DATE (crossing into the next liturgical year if needed).
+    #[arg(long, conflicts_with = "prev")]
+    next: bool,
+
+    /// Print the latest event on or before DATE.
+    #[arg(long)]
+    prev: bool,
+
+    /// Print every event from DATE through six days later, crossing into the next
+    /// liturgical year if the week straddles Advent.
+    #[arg(long, conflicts_with_all = ["next", "prev"])]
+    week: bool,
+
+    /// Print nothing and exit with status 0 if every DATE is exactly an event date,
+    /// 1 otherwise, for branching in shell scripts.
+    #[arg(long, conflicts_with_all = ["next", "prev", "week"])]
+    is_feast: bool,
+
+    /// Reference "today" (dd/mm/yyyy) used when DATE is omitted or is a relative keyword.
+    /// Falls back to the LITCAL_TODAY environment variable, then to the system date.
+    #[arg(long, value_name = "DATE")]
+    today: Option<String>,
+
+    /// IANA time zone of the parish (e.g. "America/Los_Angeles") in which the system
+    /// clock's "today" is read. Defaults to the machine's local time zone.
+    #[arg(long, value_name = "ZONE", value_parser = parse_time_zone)]
+    tz: Option<Tz>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Liturgical year used by the year-wide modes (e.g. 2024 for Advent 2024 onwards).
+    /// Without a date or another mode, lists the year's events in `--format`.
+    #[arg(long)]
+    year: Option<i32>,
+
+    /// Print the season bands of `--year` as JSON, for charting frontends.
+    #[arg(long, requires = "year")]
+    season_bands: bool,
+
+    /// List every date of `--year` whose altar color is COLOR.
+    #[arg(long, value_name = "COLOR", requires = "year")]
+    color_dates: Option<String>,
+
+    /// Print only the liturgical season of DATE (e.g. "Advent").
+    #[arg(long, value_name = "DATE")]
+    season_only: Option<String>,
+
+    /// Check the color of every event of `--year` against the documented rule for its
+    /// label, ignoring `[[color_override]]`s, and exit with status 1 on any deviation.
+    #[arg(long, requires = "year")]
+    self_check: bool,
+
+    /// Write the events of `--year` to PATH as an iCalendar (.ics) file of all-day events.
+    #[arg(long, value_name = "PATH", requires = "year")]
+    ics: Option<String>,
+
+    /// Print the legend of the altar colors used in `--year`: each color's name, hex
+    /// value (from `--palette` or the built-in values) and the seasons using it.
+    #[arg(long, requires = "year")]
+    legend: bool,
+
+    /// Print a table of the readings of every event label of `--year`, one column per set.
+    #[arg(long, requires = "year")]
+    readings_matrix: bool,
+
+    /// Print how many Sunday events `--year` contains.
+    #[arg(long, requires = "year")]
+    count_sundays: bool,
+
+    /// Print Western (Gregorian) and Orthodox (Julian) Easter of the civil YEAR and the days between them.
+    #[arg(long, value_name = "YEAR")]
+    compare_easter_algorithms: Option<i32>,
+
+    /// Print only the liturgical year and set of DATE, without computing events.
+    #[arg(long, value_name = "DATE")]
+    set_for: Option<String>,
+
+    /// TOML readings file whose entries are added on top of the bundled readings.
+    #[arg(long, value_name = "PATH")]
+    readings: Option<String>,
+
+    /// Warn on stderr for each `--readings` entry that replaces a bundled reading.
+    #[arg(long, requires = "readings")]
+    warn_overrides: bool,
+
+    /// Compare a readings file with the bundled default readings.
+    #[arg(long, value_name = "PATH")]
+    readings_diff: Option<String>,
+
+    /// Check that LABEL with `--set` is a valid custom-reading key for `--year`.
+    /// Exits with status 1 when it is not.
+    #[arg(long, value_name = "LABEL", requires_all = ["set", "year"])]
+    valid_key: Option<String>,
+
+    /// Lectionary set (1 to `--cycle-length`) used by `--valid-key`.
+    #[arg(long)]
+    set: Option<i32>,
+
+    /// List the events falling in civil year N (1 January to 31 December).
+    #[arg(long, value_name = "N")]
+    civil_year: Option<i32>,
+
+    /// In year and civil-year listings, show only events of priority N or higher
+    /// (0 = the Annunciation, 1 = Advent Sundays up to 8 = vigils and most feasts,
+    /// 9 = Candlemas).
+    #[arg(long, global = true, value_name = "N")]
+    min_priority: Option<u8>,
+
+    /// Add the Easter Vigil and the Vigil of Pentecost as events.
+    #[arg(long, global = true)]
+    include_vigils: bool,
+
+    /// Add Corpus Christi (the Thursday after Trinity Sunday) as an event.
+    #[arg(long, global = true)]
+    include_corpus_christi: bool,
+
+    /// Add Ash Wednesday, Maundy Thursday, Good Friday and Ascension Day as events.
+    #[arg(long, global = true)]
+    include_movable_feasts: bool,
+
+    /// Add Epiphany (6 January), Candlemas (2 February), the Annunciation (25 March),
+    /// Michaelmas (29 September), Reformation Day (31 October) and All Saints' Day
+    /// (1 November) on their fixed dates, and the Transfiguration on the last Sunday
+    /// before the pre-Easter series.
+    #[arg(long, global = true)]
+    include_fixed_feasts: bool,
+
+    /// Liturgical year whose Advent starts Set I of the cycle.
+    #[arg(long, global = true, value_name = "YEAR", default_value_t = DEFAULT_SET_ANCHOR)]
+    set_anchor: i32,
+
+    /// Number of sets in the lectionary cycle, e.g. 1 for a one-year lectionary.
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        default_value_t = SET_COUNT,
+        value_parser = clap::value_parser!(i32).range(1..)
+    )]
+    cycle_length: i32,
+
+    /// Which Easter the movable seasons follow.
+    #[arg(long, global = true, value_enum, default_value_t = Calendar::Western)]
+    calendar: Calendar,
+
+    /// Mark principal feasts (Christmas, Easter, Pentecost, ...) with a star in event lists.
+    #[arg(long, global = true)]
+    show_rank: bool,
+
+    /// Append each event's week offset to event lists: from Easter, e.g. "(–3 wk)",
+    /// or from Advent I for the Christmas and Epiphany seasons.
+    #[arg(long, global = true)]
+    show_offsets: bool,
+
+    /// Explain a date lookup on stderr: the anchor dates of its liturgical year and
+    /// how its set was computed.
+    #[arg(long, global = true)]
+    verbose: bool,
+
+    /// Explain on stderr how the set of a date lookup, `set` or `--set-for` follows
+    /// from its liturgical year, `--set-anchor` and `--cycle-length`.
+    #[arg(long, global = true)]
+    explain_set: bool,
+
+    /// Compute years before the Easter computation is valid (1583 for the Western
+    /// calendar, 326 for the Orthodox one) instead of rejecting them.
+    #[arg(long, global = true)]
+    allow_historical: bool,
+
+    /// Fail on equal-priority collisions instead of resolving them.
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// How altar colors are written in text output.
+    #[arg(long, global = true, value_enum, default_value_t = ColorFormat::Name)]
+    color_format: ColorFormat,
+
+    /// Render altar colors in text output in an approximating terminal color. Ignored
+    /// when stdout is not a terminal or NO_COLOR is set.
+    #[arg(long, global = true)]
+    color: bool,
+
+    /// Print a date lookup as a single JSON object instead of text; same as `--format json`.
+    #[arg(long, conflicts_with = "format")]
+    json: bool,
+
+    /// Pretty-print JSON output instead of emitting it on a single line.
+    #[arg(long, global = true)]
+    json_pretty: bool,
+
+    /// TOML calendar config file with `[[color_override]]`, `[[fixed_feast_window]]`
+    /// and `[[series]]` entries.
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<String>,
+
+    /// TOML message catalog translating the output captions, notes and error messages,
+    /// e.g. `set = "Reihe"`; keys it leaves out stay in English.
+    #[arg(long, global = true, value_name = "PATH", conflicts_with = "lang")]
+    lang_file: Option<String>,
+
+    /// Show the Sundays around Easter by their traditional names, e.g. "Palm Sunday"
+    /// for "easter - 1" and "Exaudi" for "easter + 6", the Sunday after the Ascension.
+    #[arg(long, global = true)]
+    traditional_names: bool,
+
+    /// Language of the text output: captions, event labels and altar color names.
+    #[arg(long, global = true, value_enum, default_value_t = Lang::En)]
+    lang: Lang,
+
+    /// TOML file mapping altar color names to hex values, e.g. `purple = "#5b2a86"`.
+    #[arg(long, global = true)]
+    palette: Option<String>,
+
+    /// Output layout for a date lookup or a `--year` listing.
+    #[arg(
+        long,
+        visible_alias = "output",
+        global = true,
+        value_enum,
+        default_value_t = OutputFormat::Text
+    )]
+    format: OutputFormat,
+
+    /// How to fill in readings that have no custom entry.
+    #[arg(long, value_enum, default_value_t = PlaceholderStyle::Prose)]
+    placeholder_style: PlaceholderStyle,
 }
 
-/// An event in the liturgical calendar.
-#[derive(Debug, Clone)]
-struct Event {
-    label: String,
-    date: NaiveDate,
-    altar_color: String,
-    /// Priority is used when two events fall on the same day;
-    /// higher priority events override lower ones.
-    priority: u8,
-}
-
-/// Computes the First Sunday of Advent for a given year.
-/// In this calendar, the first Advent Sunday is defined as the first Sunday on or after November 21.
-fn first_sunday_of_advent(year: i32) -> NaiveDate {
-    let candidate = NaiveDate::from_ymd(year, 11, 21);
-    let offset = (7 - candidate.weekday().num_days_from_sunday()) % 7;
-    candidate + Duration::days(offset as i64)
-}
-
-/// Returns the first Sunday on or after the given date.
-fn first_sunday_on_or_after(mut date: NaiveDate) -> NaiveDate {
-    while date.weekday() != Weekday::Sun {
-        date = date + Duration::days(1);
-    }
-    date
-}
-
-/// Computes the date of Easter for the given year (using the Meeus/Jones/Butcher algorithm).
-fn compute_easter(year: i32) -> NaiveDate {
-    let a = year % 19;
-    let b = year / 100;
-    let c = year % 100;
-    let d = b / 4;
-    let e = b % 4;
-    let f = (b + 8) / 25;
-    let g = (b - f + 1) / 3;
-    let h = (19 * a + b - d - g + 15) % 30;
-    let i = c / 4;
-    let k = c % 4;
-    let l = (32 + 2 * e + 2 * i - h - k) % 7;
-    let m = (a + 11 * h + 22 * l) / 451;
-    let month = (h + l - 7 * m + 114) / 31; // 3 = March, 4 = April
-    let day = ((h + l - 7 * m + 114) % 31) + 1;
-    NaiveDate::from_ymd(year, month as u32, day as u32)
-}
-
-/// Generates all events for the given liturgical year.
-/// The liturgical year runs from the First Sunday of Advent of the given year
-/// until (but not including) the First Sunday of Advent of the next year.
-fn generate_events(lit_year: i32) -> Vec<Event> {
-    let start = first_sunday_of_advent(lit_year);
-    let end = first_sunday_of_advent(lit_year + 1);
-
-    let mut events_map: HashMap<NaiveDate, Event> = HashMap::new();
-
-    // Helper: insert an event if its date falls between [start, end).
-    let mut insert_event = |ev: Event| {
-        if ev.date >= start && ev.date < end {
-            events_map
-                .entry(ev.date)
-                .and_modify(|existing| {
-                    if ev.priority > existing.priority {
-                        *existing = ev.clone();
-                    }
-                })
-                .or_insert(ev);
+/// Subcommands; without one, the program looks up DATE or runs the selected mode.
+#[derive(Subcommand)]
+enum Command {
+    /// List every event of liturgical year YEAR in chronological order, like `--year YEAR`.
+    List { year: i32 },
+    /// Print the date of the event labelled LABEL (e.g. "trinity + 5") in liturgical year YEAR.
+    When { year: i32, label: String },
+    /// List every event from START to END inclusive, across liturgical years if needed.
+    Range { start: String, end: String },
+    /// Print liturgical year YEAR as a Markdown table, like `--year YEAR --format markdown`.
+    ExportMd { year: i32 },
+    /// List every event of the calendar month MONTH (mm/yyyy) by weekday and day,
+    /// across the liturgical-year boundary in December.
+    Month { month: String },
+    /// Count the Sundays of SEASON (e.g. "trinity") in liturgical year YEAR.
+    CountSundays { year: i32, season: String },
+    /// Print only the set number of a date, or of a bare liturgical year such as 2025.
+    Set {
+        #[arg(value_name = "DATE_OR_YEAR")]
+        date_or_year: String,
+    },
+    /// Count the Sundays of liturgical year YEAR by altar color, most used first.
+    Stats { year: i32 },
+    /// Print the readings of the event labelled LABEL (e.g. "trinity + 12") in set SET,
+    /// without looking up a date.
+    Readings {
+        label: String,
+        #[arg(long)]
+        set: i32,
+    },
+    /// Print the events on or before and after DATE, with how many days away they are.
+    Nearest { date: String },
+}
+
+/// The Easter reckoning selected by `--calendar`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Calendar {
+    /// Gregorian Easter.
+    Western,
+    /// Julian-reckoned Pascha, converted to a Gregorian date.
+    Orthodox,
+}
+
+/// The output layouts available for a date lookup or a year listing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// The full human-readable report, or one line per event for a year.
+    Text,
+    /// A fixed plain-text block sized for a parish bulletin column.
+    Bulletin,
+    /// A single line for logs and notifications, e.g. `09/02/2025 · Set I · epiphany + 5 · green`.
+    Summary,
+    /// A fixed-width "church year at a glance" chart (year listings only).
+    Chart,
+    /// One JSON event record per line, for line-based pipelines (year listings only).
+    Jsonl,
+    /// Comma-separated `date,label,altar_color,set` rows with a header, for spreadsheets
+    /// (year and range listings only).
+    Csv,
+    /// A GitHub-flavored Markdown table of date, pericope, altar color and set
+    /// (year and range listings only).
+    Markdown,
+    /// A single JSON document: the lookup object, like `--json`, or an array of
+    /// event records for a listing.
+    Json,
+    /// An iCalendar document of all-day events on stdout (year and range listings only).
+    Ics,
+}
+
+impl OutputFormat {
+    /// The name of the format as written on the command line.
+    fn name(self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// The shape of placeholder readings for events without a custom entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum PlaceholderStyle {
+    /// Descriptive text such as "Gospel reading for advent (Set 2)".
+    Prose,
+    /// A dummy scripture reference (`TBD 0:0`) that reference parsers accept.
+    Stub,
+}
+
+/// The dummy reference emitted by `PlaceholderStyle::Stub`.
+const STUB_REFERENCE: &str = "TBD 0:0";
+
+/// The built-in English messages, keyed as in a `--lang-file` catalog.
+/// Templates mark their values with `{name}`.
+const ENGLISH_MESSAGES: &[(&str, &str)] = &[
+    ("date", "Date"),
+    ("liturgical-year", "Liturgical Year"),
+    ("set", "Set"),
+    ("season", "Season"),
+    ("pericope", "Pericope"),
+    ("altar-color", "Altar Color"),
+    ("bulletin-altar-color", "Altar color"),
+    ("readings", "Readings"),
+    ("old-testament", "Old Testament"),
+    ("lection", "Lection"),
+    ("gospel", "Gospel"),
+    ("preaching", "Preaching"),
+    (
+        "inexact-note",
+        "Note: {date} is not an exact event date. Using readings for {label} ({event_date}).",
+    ),
+    ("using-today", "(using today's date)"),
+    ("error", "Error"),
+    ("warning", "Warning"),
+    (
+        "invalid-date",
+        "Unable to parse date. Please use dd/mm/yyyy or yyyy-mm-dd format.",
+    ),
+    (
+        "no-pericope",
+        "No pericope event found for {date} in the liturgical year {year}.",
+    ),
+    ("no-readings", "No readings found for {label} (Set {set})."),
+    (
+        "no-event",
+        "No event labelled \"{label}\" in liturgical year {year}.",
+    ),
+    (
+        "invalid-set",
+        "Set {set} is outside the {length}-set cycle.",
+    ),
+    (
+        "unknown-label",
+        "\"{label}\" is not the label of any event.",
+    ),
+    ("no-season", "No season found for {date}."),
+    (
+        "invalid-month",
+        "Invalid month {month}; use mm/yyyy, e.g. 12/2025.",
+    ),
+    (
+        "invalid-range",
+        "The range start {start} is after its end {end}.",
+    ),
+    (
+        "lookup-only-format",
+        "--format {format} is only available for date lookups.",
+    ),
+    (
+        "year-only-format",
+        "--format {format} lists a whole year; use it with --year.",
+    ),
+    ("previous", "Previous"),
+    ("next", "Next"),
+    ("on-this-date", "on this date"),
+    ("day-ago", "{n} day ago"),
+    ("days-ago", "{n} days ago"),
+    ("in-day", "in {n} day"),
+    ("in-days", "in {n} days"),
+    ("bulletin-date", "{weekday} {day} {month} {year}"),
+    ("monday", "Monday"),
+    ("tuesday", "Tuesday"),
+    ("wednesday", "Wednesday"),
+    ("thursday", "Thursday"),
+    ("friday", "Friday"),
+    ("saturday", "Saturday"),
+    ("sunday", "Sunday"),
+    ("january", "January"),
+    ("february", "February"),
+    ("march", "March"),
+    ("april", "April"),
+    ("may", "May"),
+    ("june", "June"),
+    ("july", "July"),
+    ("august", "August"),
+    ("september", "September"),
+    ("october", "October"),
+    ("november", "November"),
+    ("december", "December"),
+];
+
+/// Message keys of the weekday names, from Monday.
+const WEEKDAY_KEYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// Message keys of the month names, from January.
+const MONTH_KEYS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// The languages built into `--lang`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Lang {
+    En,
+    De,
+}
+
+impl Lang {
+    /// The bundled catalog of the language; English is the built-in text itself.
+    fn catalog_source(self) -> Option<&'static str> {
+        match self {
+            Lang::En => None,
+            Lang::De => Some(include_str!("lang/de.toml")),
+        }
+    }
+}
+
+/// A message catalog from `--lang` or `--lang-file`: a TOML table of message keys to
+/// translated strings, with optional `[labels]`, `[colors]` and `[seasons]` tables
+/// translating event labels, altar color names and season names (keyed by their
+/// English name, e.g. "pre-easter"). Keys it leaves out fall back to English.
+#[derive(Debug, Default, Deserialize)]
+struct Catalog {
+    #[serde(default)]
+    labels: HashMap<String, String>,
+    #[serde(default)]
+    colors: HashMap<String, String>,
+    #[serde(default)]
+    seasons: HashMap<String, String>,
+    #[serde(flatten)]
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    fn load(path: &str) -> Result<Catalog, String> {
+        let content = read_text_file(path, "language")?;
+        Catalog::parse(&content, path)
+    }
+
+    /// Parses a catalog; `path` names it in error messages.
+    fn parse(content: &str, path: &str) -> Result<Catalog, String> {
+        let catalog: Catalog = toml::from_str(content)
+            .map_err(|e| format!("Invalid language file {}: {}", path, e))?;
+        for key in catalog.messages.keys() {
+            if !ENGLISH_MESSAGES.iter().any(|(known, _)| known == key) {
+                return Err(format!("Unknown message key {:?} in {}", key, path));
+            }
+        }
+        for name in catalog.colors.keys() {
+            name.parse::<AltarColor>()
+                .map_err(|e| format!("{} (in {})", e, path))?;
         }
+        for name in catalog.seasons.keys() {
+            name.parse::<Season>()
+                .map_err(|e| format!("{} (in {})", e, path))?;
+        }
+        Ok(catalog)
+    }
+}
+
+/// The catalog installed by `--lang` or `--lang-file`, if any.
+static CATALOG: OnceCell<Catalog> = OnceCell::new();
+
+/// Looks up a message in the installed catalog, falling back to English.
+fn message(key: &str) -> &'static str {
+    CATALOG
+        .get()
+        .and_then(|catalog| catalog.messages.get(key))
+        .map(String::as_str)
+        .unwrap_or_else(|| {
+            ENGLISH_MESSAGES
+                .iter()
+                .find(|(known, _)| *known == key)
+                .map(|(_, text)| *text)
+                .expect("message keys are defined in ENGLISH_MESSAGES")
+        })
+}
+
+/// Whether `--traditional-names` asked for "Palm Sunday" rather than "easter - 1".
+static TRADITIONAL_NAMES: AtomicBool = AtomicBool::new(false);
+
+/// Translates an event label with the installed catalog: an exact `[labels]` entry,
+/// else the series template (e.g. "trinity + {n}") filled with the label's offset,
+/// else the translated base with the English offset ("Trinitatis + 5").
+/// Untranslated Sundays around Easter show their traditional name under `--traditional-names`.
+fn label_text(label: &str) -> String {
+    let traditional = TRADITIONAL_NAMES
+        .load(Ordering::Relaxed)
+        .then(|| traditional_name(label))
+        .flatten();
+    let Some(labels) = CATALOG.get().map(|catalog| &catalog.labels) else {
+        return traditional.unwrap_or(label).to_string();
     };
+    if let (false, Some(name)) = (labels.contains_key(label), traditional) {
+        return name.to_string();
+    }
+    if let Some(text) = labels.get(label) {
+        return text.clone();
+    }
+    for sign in [" + ", " - "] {
+        if let Some((base, n)) = label.split_once(sign) {
+            if let Some(template) = labels.get(&format!("{}{}{{n}}", base, sign)) {
+                return template.replace("{n}", n);
+            }
+            if let Some(base) = labels.get(base) {
+                return format!("{}{}{}", base, sign, n);
+            }
+        }
+    }
+    label.to_string()
+}
 
-    // 1. Advent series (5 Sundays, purple), priority = 1.
-    for i in 0..=4 {
-        let ev = Event {
-            label: if i == 0 {
-                "advent".to_string()
-            } else {
-                format!("advent + {}", i)
-            },
-            date: start + Duration::days(7 * i as i64),
-            altar_color: "purple".to_string(),
-            priority: 1,
+/// The name of an altar color in the installed catalog, falling back to English.
+fn color_name(color: AltarColor) -> &'static str {
+    CATALOG
+        .get()
+        .and_then(|catalog| catalog.colors.get(color.name()))
+        .map_or(color.name(), String::as_str)
+}
+
+/// The name of a season in the installed catalog, falling back to English.
+fn season_name(season: Season) -> String {
+    let english = season.to_string();
+    CATALOG
+        .get()
+        .and_then(|catalog| {
+            catalog
+                .seasons
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(&english))
+        })
+        .map_or(english, |(_, text)| text.clone())
+}
+
+/// Looks up a message template and substitutes its `{name}` values.
+fn fill_message(key: &str, values: &[(&str, String)]) -> String {
+    values
+        .iter()
+        .fold(message(key).to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+/// The event records of a listing, each numbered within its own liturgical year.
+/// Warns about colors missing from `palette`, which falls back to the built-in values.
+fn listing_records(
+    events: Vec<Event>,
+    palette: Option<&Palette>,
+    options: &CalendarOptions,
+) -> Vec<EventRecord> {
+    if let Some(palette) = palette {
+        warn_missing_colors(palette, events.iter().map(|ev| ev.altar_color));
+    }
+    let builtin = Palette::default();
+    let palette = palette.unwrap_or(&builtin);
+    events
+        .chunk_by(|a, b| compute_liturgical_year(a.date) == compute_liturgical_year(b.date))
+        .flat_map(|year_events| {
+            let lit_year = compute_liturgical_year(year_events[0].date);
+            event_records(lit_year, year_events.to_vec(), palette, options)
+        })
+        .collect()
+}
+
+/// Warns on stderr about each color in use that the palette does not define.
+fn warn_missing_colors(palette: &Palette, used: impl IntoIterator<Item = AltarColor>) {
+    for color in palette.missing(used) {
+        eprintln!(
+            "{}: palette does not define \"{}\"; using the built-in value.",
+            message("warning"),
+            color
+        );
+    }
+}
+
+/// How altar colors are written in text output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ColorFormat {
+    /// The full color name, e.g. "purple".
+    Name,
+    /// A short code for dense grids: P, W, G, R, and Ro for rose.
+    Code,
+}
+
+/// Returns the short code of an altar color, as used by `ColorFormat::Code`.
+fn color_code(color: AltarColor) -> &'static str {
+    match color {
+        AltarColor::Purple => "P",
+        AltarColor::White => "W",
+        AltarColor::Green => "G",
+        AltarColor::Red => "R",
+        AltarColor::Rose => "Ro",
+    }
+}
+
+/// Returns the ANSI SGR code approximating an altar color in a terminal.
+fn ansi_code(color: AltarColor) -> u8 {
+    match color {
+        AltarColor::Purple => 35,
+        AltarColor::White => 97,
+        AltarColor::Green => 32,
+        AltarColor::Red => 31,
+        AltarColor::Rose => 95,
+    }
+}
+
+/// Whether `--color` output can be used: stdout is a terminal and NO_COLOR is unset.
+fn terminal_colors_enabled() -> bool {
+    std::io::stdout().is_terminal()
+        && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+/// Renders altar colors for text output, so every renderer writes them the same way.
+struct ColorRenderer<'a> {
+    format: ColorFormat,
+    /// When set, the color's hex value is appended in parentheses.
+    palette: Option<&'a Palette>,
+    /// Wrap the rendered color in its ANSI terminal color (`--color`).
+    ansi: bool,
+}
+
+impl ColorRenderer<'_> {
+    fn render(&self, color: AltarColor) -> String {
+        self.render_padded(color, 0)
+    }
+
+    /// Renders `color` padded with spaces to `width` columns; the padding stays
+    /// outside the ANSI escape so it is not counted by the terminal twice.
+    fn render_padded(&self, color: AltarColor, width: usize) -> String {
+        let rendered = self.text(color);
+        let padding = " ".repeat(width.saturating_sub(UnicodeWidthStr::width(rendered.as_str())));
+        if self.ansi {
+            format!("\x1b[{}m{}\x1b[0m{}", ansi_code(color), rendered, padding)
+        } else {
+            format!("{}{}", rendered, padding)
+        }
+    }
+
+    /// The visible text of a rendered color, without its ANSI escape.
+    fn text(&self, color: AltarColor) -> String {
+        let token = match self.format {
+            ColorFormat::Name => color_name(color),
+            ColorFormat::Code => color_code(color),
         };
-        insert_event(ev);
-    }
-
-    // 2. Christmas series (white), priority = 2.
-    // "christmas" is fixed to December 25.
-    // "christmas + 1" is the first Sunday on or after December 26.
-    // A candidate "new year" event is computed as 7 days later.
-    // If that candidate falls before January 6 of the following year, omit it so that
-    // that date becomes the start of the Epiphany series.
-    let christmas_fixed = NaiveDate::from_ymd(lit_year, 12, 25);
-    let christmas_plus1 = first_sunday_on_or_after(christmas_fixed + Duration::days(1));
-    let new_year_candidate = christmas_plus1 + Duration::days(7);
-    let new_year_threshold = NaiveDate::from_ymd(lit_year + 1, 1, 6);
-    let mut christmas_events = vec![
-        ("christmas", christmas_fixed),
-        ("christmas + 1", christmas_plus1),
-    ];
-    if new_year_candidate >= new_year_threshold {
-        christmas_events.push(("new year", new_year_candidate));
-    }
-    for (label, date) in christmas_events {
-        insert_event(Event {
-            label: label.to_string(),
-            date,
-            altar_color: "white".to_string(),
-            priority: 2,
-        });
+        match self.palette {
+            Some(palette) => format!("{} ({})", token, palette.hex(color)),
+            None => token.to_string(),
+        }
+    }
+}
+
+/// Prints Western and Orthodox Easter of `year` and the days between them.
+fn print_easter_comparison(year: i32) {
+    let western = exit_on_error(compute_easter(year));
+    let orthodox = exit_on_error(compute_easter_julian(year));
+    println!("Gregorian: {}", western.format("%d/%m/%Y"));
+    println!("Julian:    {}", orthodox.format("%d/%m/%Y"));
+    println!("Days apart: {}", (orthodox - western).num_days());
+}
+
+/// Drops the events below `min_priority`, if a minimum is given.
+fn retain_min_priority(events: &mut Vec<Event>, min_priority: Option<u8>) {
+    if let Some(min_priority) = min_priority {
+        events.retain(|ev| ev.priority >= min_priority);
     }
+}
 
-    // 3. Epiphany series (first event white, the rest green), priority = 3.
-    // If the candidate New Year date was omitted, start Epiphany on that candidate date;
-    // otherwise, use the first Sunday on or after January 6.
-    let epiphany_start = if new_year_candidate < new_year_threshold {
-        new_year_candidate
-    } else {
-        first_sunday_on_or_after(NaiveDate::from_ymd(lit_year + 1, 1, 6))
+/// Prints the events as CSV rows of date, label, altar color and set number, after a
+/// header row. Each event's set follows its own liturgical year, so a range may mix sets.
+fn print_csv(events: &[Event], cycle: SetCycle) -> Result<(), String> {
+    let mut writer = csv::Writer::from_writer(std::io::stdout().lock());
+    let write_error = |e: csv::Error| format!("Unable to write CSV: {}", e);
+    writer
+        .write_record(["date", "label", "altar_color", "set"])
+        .map_err(write_error)?;
+    for ev in events {
+        let set = cycle.set(compute_liturgical_year(ev.date));
+        writer
+            .write_record([
+                ev.date.format("%d/%m/%Y").to_string(),
+                ev.label.clone(),
+                ev.altar_color.to_string(),
+                set.to_string(),
+            ])
+            .map_err(write_error)?;
+    }
+    writer
+        .flush()
+        .map_err(|e| format!("Unable to write CSV: {}", e))
+}
+
+/// Prints the events as a GitHub-flavored Markdown table. Pipes in cells are
+/// escaped so they cannot split a column.
+fn print_markdown_table(events: &[Event], cycle: SetCycle) {
+    let escape = |cell: &str| cell.replace('|', "\\|");
+    println!("| Date | Pericope | Altar Color | Set |");
+    println!("| --- | --- | --- | --- |");
+    for ev in events {
+        let set = cycle.set(compute_liturgical_year(ev.date));
+        println!(
+            "| {} | {} | {} | {} |",
+            ev.date.format("%d/%m/%Y"),
+            escape(&label_text(&ev.label)),
+            ev.altar_color,
+            set
+        );
+    }
+}
+
+/// The narrowest label column of an event list; wider translated labels widen it.
+const EVENT_LIST_LABEL_WIDTH: usize = 16;
+
+/// The suffix `--show-rank` adds to principal feasts in event lists.
+const PRINCIPAL_MARKER: &str = " ★";
+
+/// How event lists are decorated, from `--color`, `--show-rank` and `--show-offsets`.
+struct EventListStyle<'a> {
+    colors: &'a ColorRenderer<'a>,
+    /// Mark principal feasts with `PRINCIPAL_MARKER`.
+    show_rank: bool,
+    /// Append each event's week offset, computed from the years generated with these options.
+    offsets: Option<&'a CalendarOptions>,
+}
+
+/// Formats a week offset for `--show-offsets`, e.g. "(–3 wk)" before Easter or
+/// "(Advent +4 wk)" in the Christmas and Epiphany seasons.
+fn week_offset_text(offset: WeekOffset) -> String {
+    let signed = |weeks: i64| match weeks {
+        0 => "0".to_string(),
+        weeks if weeks < 0 => format!("\u{2013}{}", -weeks),
+        weeks => format!("+{}", weeks),
     };
-    for i in 0..=6 {
-        let label = if i == 0 {
-            "epiphany".to_string()
+    match offset {
+        WeekOffset::FromAdvent(weeks) => format!("(Advent {} wk)", signed(weeks)),
+        WeekOffset::FromEaster(weeks) => format!("({} wk)", signed(weeks)),
+    }
+}
+
+/// Prints one line per event: date, label and altar color, decorated per `style`.
+fn print_event_list(events: &[Event], style: &EventListStyle) {
+    print_dated_event_list(events, style, "%d/%m/%Y");
+}
+
+/// `print_event_list` with the dates written in `date_format`, e.g. "%a %e" for
+/// the weekday and day of month in the month view.
+fn print_dated_event_list(events: &[Event], style: &EventListStyle, date_format: &str) {
+    let label_width = events
+        .iter()
+        .map(|ev| UnicodeWidthStr::width(label_text(&ev.label).as_str()))
+        .fold(EVENT_LIST_LABEL_WIDTH, usize::max);
+    for ev in events {
+        let marker = if style.show_rank && ev.is_principal() {
+            PRINCIPAL_MARKER
         } else {
-            format!("epiphany + {}", i)
+            ""
         };
-        let color = if i == 0 { "white" } else { "green" };
-        insert_event(Event {
-            label,
-            date: epiphany_start + Duration::days(7 * i as i64),
-            altar_color: color.to_string(),
-            priority: 3,
-        });
+        let offset = match style.offsets {
+            Some(options) => {
+                let anchors = year_anchors(compute_liturgical_year(ev.date), options)
+                    .expect("the events were generated for this year");
+                format!(" {}", week_offset_text(anchors.week_offset(ev.date)))
+            }
+            None => String::new(),
+        };
+        println!(
+            "{}  {} {}{}{}",
+            ev.date.format(date_format),
+            pad_to_width(&label_text(&ev.label), label_width),
+            style.colors.render(ev.altar_color),
+            marker,
+            offset
+        );
+    }
+}
+
+/// Pads `text` with spaces to `width` display columns, so wide or combining
+/// characters in labels do not break the alignment.
+fn pad_to_width(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(UnicodeWidthStr::width(text));
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+/// Describes a distance in days from the looked-up date, e.g. "3 days ago" or "in 1 day".
+fn days_away_text(days: i64) -> String {
+    let key = match days {
+        0 => "on-this-date",
+        -1 => "day-ago",
+        days if days < 0 => "days-ago",
+        1 => "in-day",
+        _ => "in-days",
+    };
+    fill_message(key, &[("n", days.abs().to_string())])
+}
+
+/// Prints the "church year at a glance" chart: one aligned row per event with
+/// the week of the liturgical year, date, season abbreviation, color code and label.
+fn print_chart(lit_year: i32, events: Vec<Event>, options: &CalendarOptions) {
+    let records = event_records(lit_year, events, &Palette::default(), options);
+    let header = ["Week", "Date", "Season", "Color", "Pericope"];
+    let rows: Vec<[String; 5]> = records
+        .into_iter()
+        .map(|record| {
+            [
+                record.week.to_string(),
+                record.civil_date,
+                record.season.map_or("-", Season::abbreviation).to_string(),
+                color_code(record.color).to_string(),
+                record.label,
+            ]
+        })
+        .collect();
+    print_table(&header, &rows);
+}
+
+/// Prints rows as columns separated by two spaces, each as wide as its widest cell.
+fn print_table<const N: usize>(header: &[&str; N], rows: &[[String; N]]) {
+    let mut widths = header.map(UnicodeWidthStr::width);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(UnicodeWidthStr::width(cell.as_str()));
+        }
+    }
+    let format_row = |cells: [&str; N]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| pad_to_width(cell, width))
+            .collect();
+        padded.join("  ").trim_end().to_string()
+    };
+    println!("{}", format_row(*header));
+    for row in rows {
+        println!("{}", format_row(row.each_ref().map(String::as_str)));
     }
+}
 
-    // 4. Pre–Easter series (9 events) with given colors, priority = 4.
-    // Labeled "easter - X" (X = 9 down to 1).
-    let pre_easter_colors = [
-        "green", "green", "white", "purple", "purple", "purple", "purple", "white", "white",
+/// Renders events as an RFC 5545 calendar of all-day events with CRLF line endings.
+/// Each UID is derived from the event's date and label, so re-importing an updated
+/// file replaces the events instead of duplicating them.
+fn ics_calendar(events: &[Event], stamp: DateTime<Utc>) -> String {
+    let stamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//pericope//liturgical calendar//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
     ];
-    let easter = compute_easter(lit_year + 1);
-    for j in 1..=9 {
-        let offset = 7 * j;
-        let date = easter - Duration::days(offset as i64);
-        let color = pre_easter_colors[(9 - j) as usize];
-        let label = format!("easter - {}", j);
-        insert_event(Event {
-            label,
-            date,
-            altar_color: color.to_string(),
-            priority: 4,
-        });
+    for ev in events {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!(
+                "UID:{}-{}@pericope",
+                ev.date.format("%Y%m%d"),
+                encode_path_segment(&ev.label)
+            ),
+            format!("DTSTAMP:{}", stamp),
+            format!("DTSTART;VALUE=DATE:{}", ev.date.format("%Y%m%d")),
+            format!(
+                "DTEND;VALUE=DATE:{}",
+                (ev.date + Duration::days(1)).format("%Y%m%d")
+            ),
+            format!("SUMMARY:{}", escape_ics_text(&ev.label)),
+            format!("DESCRIPTION:Altar color: {}", ev.altar_color),
+            "END:VEVENT".to_string(),
+        ]);
     }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold_ics_line(line)).collect()
+}
 
-    // 5. Easter series (7 events, all white), priority = 5.
-    for i in 0..=6 {
-        let label = if i == 0 {
-            "easter".to_string()
-        } else {
-            format!("easter + {}", i)
-        };
-        let date = easter + Duration::days(7 * i as i64);
-        insert_event(Event {
-            label,
-            date,
-            altar_color: "white".to_string(),
-            priority: 5,
-        });
+/// Escapes the characters that RFC 5545 reserves in TEXT values.
+fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Terminates a content line with CRLF, folding it so no line exceeds 75 octets.
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
     }
+    folded.push_str("\r\n");
+    folded
+}
 
-    // 6. Pentecost (red), priority = 6.
-    let pentecost = easter + Duration::days(49); // 7 weeks after Easter
-    insert_event(Event {
-        label: "pentecost".to_string(),
-        date: pentecost,
-        altar_color: "red".to_string(),
-        priority: 6,
-    });
-
-    // 7. Trinity series (28 events), priority = 7.
-    let trinity_start = pentecost + Duration::days(7);
-    for i in 0..=27 {
-        let label = if i == 0 {
-            "trinity".to_string()
-        } else {
-            format!("trinity + {}", i)
-        };
-        let date = trinity_start + Duration::days(7 * i as i64);
-        let color = if i == 0 {
-            "white"
-        } else if (1..=4).contains(&i) {
-            "green"
-        } else if i == 5 {
-            "red"
-        } else {
-            "green"
+/// Prints the colors of `events` in order of first use, with their hex value and
+/// the seasons in which they appear.
+fn print_color_legend(events: &[Event], palette: &Palette) {
+    let mut legend: Vec<(AltarColor, Vec<Season>)> = Vec::new();
+    for ev in events {
+        let index = match legend
+            .iter()
+            .position(|(color, _)| *color == ev.altar_color)
+        {
+            Some(index) => index,
+            None => {
+                legend.push((ev.altar_color, Vec::new()));
+                legend.len() - 1
+            }
         };
-        insert_event(Event {
-            label,
-            date,
-            altar_color: color.to_string(),
-            priority: 7,
+        let seasons = &mut legend[index].1;
+        if let Some(season) = ev.season() {
+            if !seasons.contains(&season) {
+                seasons.push(season);
+            }
+        }
+    }
+    let rows: Vec<[String; 3]> = legend
+        .into_iter()
+        .map(|(color, seasons)| {
+            let seasons: Vec<String> = seasons.into_iter().map(season_name).collect();
+            [
+                color_name(color).to_string(),
+                palette.hex(color),
+                seasons.join(", "),
+            ]
+        })
+        .collect();
+    print_table(&["Color", "Hex", "Seasons"], &rows);
+}
+
+/// The cell shown by the readings matrix for an entry without custom readings.
+const MATRIX_PLACEHOLDER: &str = "(placeholder)";
+
+/// Prints one row per event label of `events` with a column per set, each cell
+/// holding the custom readings (Old Testament; Lection; Gospel; Preaching) or
+/// `MATRIX_PLACEHOLDER`.
+fn print_readings_matrix(events: &[Event], provider: &dyn ReadingProvider) {
+    let mut labels: Vec<&str> = Vec::new();
+    for ev in events {
+        if !labels.contains(&ev.label.as_str()) {
+            labels.push(&ev.label);
+        }
+    }
+    let rows: Vec<[String; 4]> = labels
+        .into_iter()
+        .map(|label| {
+            let cell = |set: i32| match provider.readings(label, set) {
+                Some(r) => format!(
+                    "{}; {}; {}; {}",
+                    r.old_testament, r.lection, r.gospel, r.preaching
+                ),
+                None => MATRIX_PLACEHOLDER.to_string(),
+            };
+            [label.to_string(), cell(1), cell(2), cell(3)]
+        })
+        .collect();
+    print_table(&["Pericope", "Set I", "Set II", "Set III"], &rows);
+}
+
+/// Adds a user's readings on top of `base`, replacing entries with the same key.
+/// With `warn`, each replaced entry is reported on stderr with its old and new
+/// Old Testament reading, so unintended shadowing is noticed.
+fn overlay_readings(base: &mut CustomReadings, user: CustomReadings, warn: bool) {
+    for (key, readings) in user {
+        if let Some(previous) = base.insert(key.clone(), readings) {
+            if warn {
+                eprintln!(
+                    "{}: readings file overrides {} (Set {}): Old Testament \"{}\" -> \"{}\"",
+                    message("warning"),
+                    key.0,
+                    key.1,
+                    previous.0,
+                    base[&key].0
+                );
+            }
+        }
+    }
+}
+
+/// Prints a readings diff, showing old and new values for changed entries.
+fn print_readings_diff(
+    diff: &ReadingsDiff,
+    path: &str,
+    user: &CustomReadings,
+    defaults: &CustomReadings,
+) {
+    println!("Only in {}:", path);
+    for (label, set) in &diff.only_user {
+        println!("  {} (Set {})", label, set);
+    }
+    println!("Only in defaults:");
+    for (label, set) in &diff.only_default {
+        println!("  {} (Set {})", label, set);
+    }
+    println!("Different:");
+    for key in &diff.changed {
+        println!("  {} (Set {})", key.0, key.1);
+        let (old, new) = (&defaults[key], &user[key]);
+        let fields = [
+            ("Old Testament", &old.0, &new.0),
+            ("Lection", &old.1, &new.1),
+            ("Gospel", &old.2, &new.2),
+            ("Preaching", &old.3, &new.3),
+        ];
+        for (name, old, new) in fields {
+            if old != new {
+                println!("    {}: {} -> {}", name, old, new);
+            }
+        }
+    }
+}
+
+/// Placeholder readings in `PlaceholderStyle::Stub`: `STUB_REFERENCE` for all four.
+struct StubReadingProvider;
+
+impl ReadingProvider for StubReadingProvider {
+    fn readings(&self, _label: &str, _set: i32) -> Option<Readings> {
+        Some(Readings {
+            old_testament: STUB_REFERENCE.to_string(),
+            lection: STUB_REFERENCE.to_string(),
+            gospel: STUB_REFERENCE.to_string(),
+            preaching: STUB_REFERENCE.to_string(),
+        })
+    }
+}
+
+/// Prints the four readings in the indented text layout.
+fn print_readings(readings: &Readings) {
+    let lines = reading_lines(readings);
+    let width = lines
+        .iter()
+        .map(|(caption, _)| UnicodeWidthStr::width(*caption) + 1)
+        .max()
+        .unwrap_or(0);
+    for (caption, reading) in lines {
+        println!(
+            "  {} {}",
+            pad_to_width(&format!("{}:", caption), width),
+            reading
+        );
+    }
+}
+
+/// Pairs each of the four readings with its caption from the message catalog.
+fn reading_lines(readings: &Readings) -> [(&'static str, &str); 4] {
+    [
+        (message("old-testament"), &readings.old_testament),
+        (message("lection"), &readings.lection),
+        (message("gospel"), &readings.gospel),
+        (message("preaching"), &readings.preaching),
+    ]
+}
+
+/// Parses a date argument, exiting with an error message if it is invalid.
+fn parse_date_or_exit(text: &str) -> NaiveDate {
+    parse_input_date(text).unwrap_or_else(|e| fail("invalid-date", e))
+}
+
+/// Parses a calendar month given as mm/yyyy into its first and last day.
+fn parse_month(text: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let invalid = || fill_message("invalid-month", &[("month", format!("{:?}", text))]);
+    let (month, year) = text.trim().split_once('/').ok_or_else(invalid)?;
+    let (month, year): (u32, i32) = (
+        month.parse().map_err(|_| invalid())?,
+        year.parse().map_err(|_| invalid())?,
+    );
+    let first = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(invalid)?;
+    let last = first
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .ok_or_else(invalid)?;
+    Ok((first, last))
+}
+
+/// Parses an input date as dd/mm/yyyy, or as ISO yyyy-mm-dd (e.g. pasted from a spreadsheet).
+fn parse_input_date(text: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(text, "%d/%m/%Y")
+        .or_else(|_| NaiveDate::parse_from_str(text, "%Y-%m-%d"))
+        .map_err(|_| message("invalid-date").to_string())
+}
+
+/// Parses the DATE argument: a keyword relative to `today` (`today`, `tomorrow`,
+/// `yesterday`, `next-sunday`) or a date accepted by `parse_input_date`.
+/// `next-sunday` is the first Sunday after `today`, a week ahead on a Sunday.
+fn parse_date_arg(text: &str, today: impl FnOnce() -> NaiveDate) -> Result<NaiveDate, LookupError> {
+    match text.trim().to_ascii_lowercase().as_str() {
+        "today" => Ok(today()),
+        "tomorrow" => Ok(today() + Duration::days(1)),
+        "yesterday" => Ok(today() - Duration::days(1)),
+        "next-sunday" => Ok(first_sunday_on_or_after(today() + Duration::days(1))),
+        _ => parse_input_date(text).map_err(LookupError::InvalidDate),
+    }
+}
+
+/// The environment variable that pins "today" when `--today` is not given.
+const TODAY_ENV_VAR: &str = "LITCAL_TODAY";
+
+/// Resolves the reference date: `--today`, then `LITCAL_TODAY`, then the system
+/// date, read in `tz` when given so a server in UTC reports the parish's civil day.
+fn reference_today(flag: Option<&str>, tz: Option<Tz>) -> NaiveDate {
+    if let Some(text) = flag {
+        return parse_date_or_exit(text);
+    }
+    match (std::env::var(TODAY_ENV_VAR), tz) {
+        (Ok(text), _) => parse_date_or_exit(&text),
+        (Err(_), Some(tz)) => civil_date_in(Utc::now(), tz),
+        (Err(_), None) => chrono::Local::now().date_naive(),
+    }
+}
+
+/// The civil date at `instant` in the time zone `tz`.
+fn civil_date_in(instant: DateTime<Utc>, tz: Tz) -> NaiveDate {
+    instant.with_timezone(&tz).date_naive()
+}
+
+/// Parses an IANA time zone name for `--tz`.
+fn parse_time_zone(name: &str) -> Result<Tz, String> {
+    name.parse::<Tz>()
+        .map_err(|_| format!("unknown time zone {:?}", name))
+}
+
+/// Prints an error message on stderr behind the catalog's "Error" caption.
+fn print_error(error: impl std::fmt::Display) {
+    eprintln!("{}: {}", message("error"), error);
+}
+
+/// Whether errors are reported as a JSON envelope on stdout; set for the JSON output modes.
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// A machine-readable classification of an error, reported as `kind` in the JSON
+/// error envelope.
+trait ErrorKind {
+    fn kind(&self) -> &'static str;
+}
+
+/// Errors from parsing arguments and loading files are plain messages.
+impl ErrorKind for String {
+    fn kind(&self) -> &'static str {
+        "invalid-input"
+    }
+}
+
+impl ErrorKind for CalendarError {
+    fn kind(&self) -> &'static str {
+        match self {
+            CalendarError::Collision { .. } => "collision",
+            CalendarError::YearOutOfRange(_) | CalendarError::FollowingYearOutOfRange { .. } => {
+                "year-out-of-range"
+            }
+            CalendarError::HistoricalYear { .. } => "historical-year",
+            CalendarError::MissingAdventSeries => "missing-advent-series",
+        }
+    }
+}
+
+/// Why a date lookup failed. Calendar errors keep their own kind in the JSON error
+/// envelope rather than being flattened into a message.
+#[derive(Debug)]
+enum LookupError {
+    Calendar(CalendarError),
+    /// A date argument or dates-file line that is not a date.
+    InvalidDate(String),
+    /// No event on or before the date in its liturgical year.
+    NoPericope(String),
+    /// Neither the readings nor the placeholders have an entry for the event.
+    NoReadings(String),
+}
+
+impl From<CalendarError> for LookupError {
+    fn from(error: CalendarError) -> Self {
+        LookupError::Calendar(error)
+    }
+}
+
+impl std::fmt::Display for LookupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LookupError::Calendar(error) => error.fmt(f),
+            LookupError::InvalidDate(message)
+            | LookupError::NoPericope(message)
+            | LookupError::NoReadings(message) => f.write_str(message),
+        }
+    }
+}
+
+impl ErrorKind for LookupError {
+    fn kind(&self) -> &'static str {
+        match self {
+            LookupError::Calendar(error) => error.kind(),
+            LookupError::InvalidDate(_) => "invalid-date",
+            LookupError::NoPericope(_) => "no-pericope",
+            LookupError::NoReadings(_) => "no-readings",
+        }
+    }
+}
+
+/// Reports an error without exiting. In JSON output modes the error goes to stdout
+/// as `{"error": {"kind": ..., "message": ...}}`, so JSON consumers need not parse
+/// stderr; otherwise it is printed on stderr.
+fn report_error(kind: &str, error: impl std::fmt::Display) {
+    if JSON_ERRORS.load(Ordering::Relaxed) {
+        let envelope = serde_json::json!({
+            "error": { "kind": kind, "message": error.to_string() }
         });
+        print_json(&envelope, false);
+    } else {
+        print_error(error);
     }
+}
 
-    let mut events: Vec<Event> = events_map.into_values().collect();
-    events.sort_by_key(|ev| ev.date);
-    events
+/// Reports an error with `report_error` and exits with a non-zero status.
+fn fail(kind: &str, error: impl std::fmt::Display) -> ! {
+    report_error(kind, error);
+    std::process::exit(1);
+}
+
+/// Unwraps a result, or reports its error and exits with a non-zero status.
+fn exit_on_error<T, E: std::fmt::Display + ErrorKind>(result: Result<T, E>) -> T {
+    result.unwrap_or_else(|e| fail(e.kind(), e))
+}
+
+/// Prints a value as JSON, compact by default or indented when `pretty` is set.
+fn print_json<T: Serialize>(value: &T, pretty: bool) {
+    let json = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    println!("{}", json.expect("output types serialize to JSON"));
+}
+
+/// The result of looking up a date: the matched event and its readings.
+struct Lookup<'a> {
+    input_date: NaiveDate,
+    lit_year: i32,
+    set: i32,
+    event: &'a Event,
+    /// False when the date fell between events and the most recent one was used.
+    is_exact_match: bool,
+    readings: Readings,
+    anchors: YearAnchors,
+}
+
+/// The JSON form of a lookup, printed by `--json`.
+#[derive(Serialize)]
+struct LookupRecord<'a> {
+    /// The looked-up date (ISO).
+    date: NaiveDate,
+    liturgical_year: i32,
+    set: i32,
+    pericope: &'a str,
+    /// Whether the event is a principal feast rather than an ordinary Sunday.
+    is_principal: bool,
+    altar_color: AltarColor,
+    /// The altar color's hex value from `--palette`, or its built-in value.
+    color_hex: String,
+    /// False when the date fell between events and the most recent one was used.
+    is_exact_match: bool,
+    /// The date of the event whose readings are given.
+    event_date: NaiveDate,
+    /// Signed whole weeks from Easter to the event, e.g. -3 for "easter - 3".
+    weeks_from_easter: i64,
+    /// Whole weeks from Advent I before the pre-Easter series, from Easter after.
+    #[serde(flatten)]
+    week_offset: WeekOffset,
+    readings: &'a Readings,
+}
+
+impl<'a> LookupRecord<'a> {
+    fn new(lookup: &'a Lookup, palette: &Palette) -> LookupRecord<'a> {
+        LookupRecord {
+            date: lookup.input_date,
+            liturgical_year: lookup.lit_year,
+            set: lookup.set,
+            pericope: &lookup.event.label,
+            is_principal: lookup.event.is_principal(),
+            altar_color: lookup.event.altar_color,
+            color_hex: palette.hex(lookup.event.altar_color),
+            is_exact_match: lookup.is_exact_match,
+            event_date: lookup.event.date,
+            weeks_from_easter: lookup.anchors.weeks_from_easter(lookup.event.date),
+            week_offset: lookup.anchors.week_offset(lookup.event.date),
+            readings: &lookup.readings,
+        }
+    }
 }
 
-/// Determines the liturgical year for an input date.
-/// If the input date is on or after the First Sunday of Advent for that calendar year,
-/// the liturgical year is the calendar year; otherwise it is the previous calendar year.
-fn compute_liturgical_year(input: NaiveDate) -> i32 {
-    let candidate = first_sunday_of_advent(input.year());
-    if input >= candidate {
-        input.year()
+/// Prints a lookup in the default text layout.
+fn print_lookup_text(lookup: &Lookup, colors: &ColorRenderer) {
+    let ev = lookup.event;
+    if lookup.is_exact_match {
+        println!(
+            "{}: {}",
+            message("date"),
+            lookup.input_date.format("%d/%m/%Y")
+        );
     } else {
-        input.year() - 1
+        println!(
+            "{}",
+            fill_message(
+                "inexact-note",
+                &[
+                    ("date", lookup.input_date.format("%d/%m/%Y").to_string()),
+                    ("label", label_text(&ev.label)),
+                    ("event_date", ev.date.format("%d/%m/%Y").to_string()),
+                ],
+            )
+        );
+    }
+    println!("{}: {}", message("liturgical-year"), lookup.lit_year);
+    println!("{}: {}", message("set"), lookup.set);
+    if let Some(season) = ev.season() {
+        println!("{}: {}", message("season"), season_name(season));
     }
+    println!("{}: {}", message("pericope"), label_text(&ev.label));
+    println!(
+        "{}: {}",
+        message("altar-color"),
+        colors.render(ev.altar_color)
+    );
+    println!("{}:", message("readings"));
+    print_readings(&lookup.readings);
 }
 
-/// Computes the set number from the liturgical year.
-/// According to our rule:
-///   Advent 2024 → set I, 2025 → set II, 2026 → set III, then repeat.
-fn compute_set(lit_year: i32) -> i32 {
-    (((lit_year - 2024).rem_euclid(3)) + 1)
+/// Spells out the set computation of `lit_year`, which began on `advent`, for `--explain-set`.
+fn set_explanation(lit_year: i32, advent: NaiveDate, cycle: SetCycle) -> String {
+    let set = cycle.set(lit_year);
+    format!(
+        "Liturgical year {} began on {}; the {}-set cycle anchored on {} puts {} in Set {}: \
+         ({} - {}) mod {} + 1 = {}.",
+        lit_year,
+        advent.format("%d/%m/%Y"),
+        cycle.length,
+        cycle.anchor,
+        lit_year,
+        roman_numeral(set),
+        lit_year,
+        cycle.anchor,
+        cycle.length,
+        set
+    )
 }
 
-fn main() {
-    let args = Args::parse();
+/// Prints `set_explanation` for `lit_year` on stderr, computing its Advent I.
+fn explain_set_of(lit_year: i32, cycle: SetCycle) {
+    let advent = exit_on_error(first_sunday_of_advent(lit_year));
+    eprintln!("{}", set_explanation(lit_year, advent, cycle));
+}
 
-    // Parse the input date.
-    let input_date = match NaiveDate::parse_from_str(&args.date, "%d/%m/%Y") {
-        Ok(d) => d,
-        Err(_) => {
-            eprintln!("Error: Unable to parse date. Please use dd/mm/yyyy format.");
-            std::process::exit(1);
+/// Prints the anchor dates of liturgical year `lit_year` on stderr, for `--verbose`.
+fn print_year_anchors(lit_year: i32, anchors: &YearAnchors) {
+    let date = |date: NaiveDate| date.format("%d/%m/%Y").to_string();
+    eprintln!(
+        "Liturgical year: {} ({} to {})",
+        lit_year,
+        date(anchors.advent),
+        date(anchors.end - Duration::days(1))
+    );
+    eprintln!("Advent I:        {}", date(anchors.advent));
+    eprintln!("Easter:          {}", date(anchors.easter));
+    eprintln!("Pentecost:       {}", date(anchors.pentecost));
+    eprintln!("Trinity:         {}", date(anchors.trinity));
+}
+
+/// The settings shared by every date lookup of a run.
+struct LookupContext<'a> {
+    options: &'a CalendarOptions,
+    /// Readings for events that fall exactly on the looked-up date.
+    readings: &'a dyn ReadingProvider,
+    /// Readings for everything `readings` has no entry for, per `--placeholder-style`.
+    placeholders: &'a dyn ReadingProvider,
+    cycle: SetCycle,
+    palette: Option<&'a Palette>,
+    colors: &'a ColorRenderer<'a>,
+    format: OutputFormat,
+    json_pretty: bool,
+    /// Explain each lookup on stderr (`--verbose`).
+    verbose: bool,
+    /// Explain each lookup's set on stderr (`--explain-set`).
+    explain_set: bool,
+    /// Generated liturgical years, reused by later lookups in the same year.
+    years: RefCell<CalendarCache>,
+    /// Whether a lookup has been printed, so the next one is separated from it.
+    printed: Cell<bool>,
+}
+
+impl LookupContext<'_> {
+    /// Looks up `input_date` and prints it in the chosen format.
+    fn print(&self, input_date: NaiveDate) -> Result<(), LookupError> {
+        // Determine the liturgical year and set.
+        let lit_year = compute_liturgical_year(input_date);
+        let set = self.cycle.set(lit_year);
+        let anchors = year_anchors(lit_year, self.options)?;
+        if self.verbose {
+            print_year_anchors(lit_year, &anchors);
+            eprintln!(
+                "Set:             {} (anchor {}, {}-year cycle)",
+                set, self.cycle.anchor, self.cycle.length
+            );
+        }
+        if self.explain_set {
+            eprintln!("{}", set_explanation(lit_year, anchors.advent, self.cycle));
         }
-    };
 
-    // Define a mapping for custom Bible readings.
-    // Key: (event label, set number)
-    // Value: (Old Testament, Lection, Gospel, Preaching)
-    let custom_readings: HashMap<(String, i32), (String, String, String, String)> =
-        HashMap::from([
+        if self.printed.replace(true)
+            && !matches!(self.format, OutputFormat::Json | OutputFormat::Summary)
+        {
+            println!();
+        }
+
+        // Generate events for the liturgical year, once per year of the run.
+        let mut years = self.years.borrow_mut();
+        let events = years.get_or_generate(lit_year)?;
+
+        // Look for an event exactly matching the input date, otherwise use the most recent one.
+        // `compute_liturgical_year` places the date inside the year's window, and
+        // `generate_events` fails rather than return a year without an event on its
+        // first day, so this branch is only reached if that check is ever lost.
+        let found = previous_event(events, input_date);
+        let exact = found.filter(|ev| ev.date == input_date);
+        let Some(ev) = found else {
+            return Err(LookupError::NoPericope(fill_message(
+                "no-pericope",
+                &[
+                    ("date", input_date.format("%d/%m/%Y").to_string()),
+                    ("year", lit_year.to_string()),
+                ],
+            )));
+        };
+
+        // Custom Bible readings are keyed by (event, set); otherwise use placeholders.
+        // `generate_events` keeps only the highest-priority event per date, so when a
+        // feast displaces a Sunday (e.g. All Saints 2026 on "trinity + 22") the feast's
+        // label is the key and the displaced Sunday's readings are never used.
+        let readings = exact
+            .and_then(|ev| self.readings.readings(&ev.label, set))
+            .or_else(|| self.placeholders.readings(&ev.label, set))
+            .ok_or_else(|| {
+                LookupError::NoReadings(fill_message(
+                    "no-readings",
+                    &[("label", ev.label.clone()), ("set", set.to_string())],
+                ))
+            })?;
+        if let Some(palette) = self.palette {
+            warn_missing_colors(palette, [ev.altar_color]);
+        }
+
+        let lookup = Lookup {
+            input_date,
+            lit_year,
+            set,
+            event: ev,
+            is_exact_match: exact.is_some(),
+            readings,
+            anchors,
+        };
+        match self.format {
+            OutputFormat::Json => {
+                let builtin = Palette::default();
+                let palette = self.palette.unwrap_or(&builtin);
+                print_json(&LookupRecord::new(&lookup, palette), self.json_pretty);
+            }
+            OutputFormat::Text => print_lookup_text(&lookup, self.colors),
+            OutputFormat::Bulletin => print_lookup_bulletin(&lookup, self.colors),
+            OutputFormat::Summary => println!("{}", lookup_summary(&lookup, self.colors)),
+            OutputFormat::Chart
+            | OutputFormat::Jsonl
+            | OutputFormat::Csv
+            | OutputFormat::Markdown
+            | OutputFormat::Ics => {
+                unreachable!("year-only formats are rejected before lookups")
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the `--dates-file` input: the file at `path`, or stdin when it is `-`.
+fn read_dates_input(path: &str) -> Result<String, String> {
+    if path != "-" {
+        return read_text_file(path, "dates file");
+    }
+    let content = std::io::read_to_string(std::io::stdin())
+        .map_err(|e| format!("Unable to read dates from stdin: {}", e))?;
+    Ok(normalize_text(&content))
+}
+
+/// Looks up every date of a dates file, one date per line.
+/// Blank lines and `#` comments are skipped; a bad line is reported with its
+/// number, in the JSON error envelope where enabled, and the run continues.
+/// Returns whether every line succeeded.
+fn run_dates_file(text: &str, context: &LookupContext) -> bool {
+    let mut all_ok = true;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let result = parse_input_date(line)
+            .map_err(LookupError::InvalidDate)
+            .and_then(|date| context.print(date));
+        if let Err(e) = result {
+            report_error(e.kind(), format!("line {}: {}", index + 1, e));
+            all_ok = false;
+        }
+    }
+    all_ok
+}
+
+/// Width of the rule separating the bulletin heading from the readings.
+const BULLETIN_WIDTH: usize = 36;
+
+/// Writes out a date with the catalog's weekday and month names, e.g.
+/// "Sunday 15 February 2026".
+fn bulletin_date(date: NaiveDate) -> String {
+    fill_message(
+        "bulletin-date",
+        &[
             (
-                ("epiphany + 5".to_string(), 1),
-                (
-                    "Jer 17:5-10".to_string(),
-                    "Col 3:12-17".to_string(),
-                    "Mat 13:31-35".to_string(),
-                    "Mat 13:24-30".to_string(),
-                ),
+                "weekday",
+                message(WEEKDAY_KEYS[date.weekday().num_days_from_monday() as usize]).to_string(),
             ),
-            // Add more custom entries here as needed.
+            ("day", format!("{:02}", date.day())),
             (
-                ("easter - 9".to_string(), 1),
-                (
-                    "Jer 1:4-10".to_string(),
-                    "1 Cor:09:24-10:05".to_string(),
-                    "Mat 19:27-30".to_string(),
-                    "Mat 20:1-16".to_string(),
-                ),
+                "month",
+                message(MONTH_KEYS[date.month0() as usize]).to_string(),
             ),
-        ]);
+            ("year", date.year().to_string()),
+        ],
+    )
+}
 
-    // Determine the liturgical year and set.
-    let lit_year = compute_liturgical_year(input_date);
-    let set = compute_set(lit_year);
-
-    // Generate events for the liturgical year.
-    let events = generate_events(lit_year);
-
-    // Look for an event exactly matching the input date.
-    if let Some(ev) = events.iter().find(|ev| ev.date == input_date) {
-        println!("Date: {}", input_date.format("%d/%m/%Y"));
-        println!("Liturgical Year: {}", lit_year);
-        println!("Set: {}", set);
-        println!("Pericope: {}", ev.label);
-        println!("Altar Color: {}", ev.altar_color);
-        println!("Readings:");
-        
-        // Check if a custom Bible reading exists for (event, set).
-        let key = (ev.label.clone(), set);
-        if let Some((ot, le, go, pr)) = custom_readings.get(&key) {
-            println!("  Old Testament: {}", ot);
-            println!("  Lection:       {}", le);
-            println!("  Gospel:        {}", go);
-            println!("  Preaching:     {}", pr);
-        } else {
-            // Otherwise, print default placeholder readings.
-            let gospel_set = if set == 3 { 1 } else { set + 1 };
-            println!(
-                "  Old Testament: Old Testament reading for {} (Set {})",
-                ev.label, set
-            );
-            println!(
-                "  Lection:       Lection reading for {} (Set {})",
-                ev.label, set
-            );
+/// Prints a lookup as a copy-paste-ready parish bulletin block, headed by the
+/// looked-up date.
+fn print_lookup_bulletin(lookup: &Lookup, colors: &ColorRenderer) {
+    let ev = lookup.event;
+    let season = ev
+        .season()
+        .map_or_else(|| label_text(&ev.label), season_name);
+    println!("{}", bulletin_date(lookup.input_date));
+    println!("{} ({} {})", season, message("set"), lookup.set);
+    println!(
+        "{}: {}",
+        message("bulletin-altar-color"),
+        colors.render(ev.altar_color)
+    );
+    println!("{}", "-".repeat(BULLETIN_WIDTH));
+    let lines = reading_lines(&lookup.readings);
+    let width = lines
+        .iter()
+        .map(|(caption, _)| UnicodeWidthStr::width(*caption) + 2)
+        .max()
+        .unwrap_or(0);
+    for (caption, reading) in lines {
+        println!("{}{}", pad_to_width(caption, width), reading);
+    }
+}
+
+/// Formats a lookup as a one-line summary separated by middle dots.
+fn lookup_summary(lookup: &Lookup, colors: &ColorRenderer) -> String {
+    format!(
+        "{} · {} {} · {} · {}",
+        lookup.input_date.format("%d/%m/%Y"),
+        message("set"),
+        roman_numeral(lookup.set),
+        label_text(&lookup.event.label),
+        colors.render(lookup.event.altar_color)
+    )
+}
+
+/// Writes a positive number in Roman numerals (used for set numbers, e.g. 2 → "II").
+fn roman_numeral(mut n: i32) -> String {
+    const NUMERALS: [(i32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut roman = String::new();
+    for (value, numeral) in NUMERALS {
+        while n >= value {
+            roman.push_str(numeral);
+            n -= value;
+        }
+    }
+    roman
+}
+
+fn main() {
+    let mut args = Args::parse();
+    let mut when = None;
+    let mut range = None;
+    let mut month = None;
+    let mut season_sundays = None;
+    let mut set_of = None;
+    let mut nearest = None;
+    let mut stats = None;
+    let mut label_readings = None;
+    match args.command.take() {
+        Some(Command::List { year }) => args.year = Some(year),
+        Some(Command::ExportMd { year }) => {
+            args.year = Some(year);
+            args.format = OutputFormat::Markdown;
+        }
+        Some(Command::When { year, label }) => when = Some((year, label)),
+        Some(Command::Range { start, end }) => range = Some((start, end)),
+        Some(Command::Month { month: text }) => month = Some(text),
+        Some(Command::CountSundays { year, season }) => season_sundays = Some((year, season)),
+        Some(Command::Set { date_or_year }) => set_of = Some(date_or_year),
+        Some(Command::Nearest { date }) => nearest = Some(date),
+        Some(Command::Stats { year }) => stats = Some(year),
+        Some(Command::Readings { label, set }) => label_readings = Some((label, set)),
+        None => {}
+    }
+    if args.json {
+        args.format = OutputFormat::Json;
+    }
+    JSON_ERRORS.store(
+        args.season_bands || matches!(args.format, OutputFormat::Json | OutputFormat::Jsonl),
+        Ordering::Relaxed,
+    );
+    TRADITIONAL_NAMES.store(args.traditional_names, Ordering::Relaxed);
+
+    let catalog = match (&args.lang_file, args.lang.catalog_source()) {
+        (Some(path), _) => Some(exit_on_error(Catalog::load(path))),
+        (None, Some(source)) => {
+            Some(Catalog::parse(source, "the bundled catalog").expect("bundled catalogs are valid"))
+        }
+        (None, None) => None,
+    };
+    if let Some(catalog) = catalog {
+        CATALOG
+            .set(catalog)
+            .expect("the catalog is installed once, before any output");
+    }
+
+    let palette = match &args.palette {
+        Some(path) => match Palette::load(path) {
+            Ok(palette) => Some(palette),
+            Err(e) => fail(e.kind(), e),
+        },
+        None => None,
+    };
+
+    let config = match &args.config {
+        Some(path) => exit_on_error(load_config(path)),
+        None => CalendarConfig::default(),
+    };
+    let options = CalendarOptions {
+        include_vigils: args.include_vigils,
+        include_corpus_christi: args.include_corpus_christi,
+        include_movable_feasts: args.include_movable_feasts,
+        include_fixed_feasts: args.include_fixed_feasts,
+        allow_historical: args.allow_historical,
+        strict: args.strict,
+        easter_reckoning: match args.calendar {
+            Calendar::Western => EasterReckoning::Western,
+            Calendar::Orthodox => EasterReckoning::Orthodox,
+        },
+        color_overrides: config.color_overrides,
+        fixed_feast_windows: config.fixed_feast_windows,
+        series: config.series,
+    };
+
+    let colors = ColorRenderer {
+        format: args.color_format,
+        palette: palette.as_ref(),
+        ansi: args.color && terminal_colors_enabled(),
+    };
+    let list_style = EventListStyle {
+        colors: &colors,
+        show_rank: args.show_rank,
+        offsets: args.show_offsets.then_some(&options),
+    };
+
+    let mut custom_readings = default_readings();
+    if let Some(path) = &args.readings {
+        let user = exit_on_error(load_readings(path));
+        overlay_readings(&mut custom_readings, user, args.warn_overrides);
+    }
+    let reading_provider = HashMapReadingProvider::new(custom_readings);
+    let cycle = SetCycle {
+        anchor: args.set_anchor,
+        length: args.cycle_length,
+    };
+    let prose = DefaultReadingProvider { cycle };
+    let placeholders: &dyn ReadingProvider = match args.placeholder_style {
+        PlaceholderStyle::Prose => &prose,
+        PlaceholderStyle::Stub => &StubReadingProvider,
+    };
+
+    if args.season_bands {
+        let year = args.year.expect("clap requires --year with --season-bands");
+        if let Some(palette) = &palette {
+            let events = exit_on_error(generate_events(year, &options));
+            warn_missing_colors(palette, events.iter().map(|ev| ev.altar_color));
+        }
+        let bands = exit_on_error(season_bands(
+            year,
+            &options,
+            palette.as_ref().unwrap_or(&Palette::default()),
+        ));
+        print_json(&bands, args.json_pretty);
+        return;
+    }
+
+    if let Some(color) = &args.color_dates {
+        let year = args.year.expect("clap requires --year with --color-dates");
+        let color = exit_on_error(color.parse::<AltarColor>());
+        for date in exit_on_error(dates_with_color(year, &options, color)) {
+            println!("{}", date.format("%d/%m/%Y"));
+        }
+        return;
+    }
+
+    if let Some(path) = &args.readings_diff {
+        let user = match load_readings(path) {
+            Ok(readings) => readings,
+            Err(e) => fail(e.kind(), e),
+        };
+        let defaults = default_readings();
+        let diff = diff_readings(&user, &defaults);
+        print_readings_diff(&diff, path, &user, &defaults);
+        return;
+    }
+
+    if let Some(label) = &args.valid_key {
+        let set = args.set.expect("clap requires --set with --valid-key");
+        let year = args.year.expect("clap requires --year with --valid-key");
+        if is_valid_reading_key(label, set, year, &options, cycle) {
             println!(
-                "  Gospel:        Gospel reading for {} (Set {})",
-                ev.label, gospel_set
+                "\"{}\" (Set {}) is a valid reading key for {}.",
+                label, set, year
             );
+        } else {
             println!(
-                "  Preaching:     Preaching reading for {} (Set {})",
-                ev.label, set
+                "\"{}\" (Set {}) is not a valid reading key for {}.",
+                label, set, year
             );
+            std::process::exit(1);
         }
-    } else {
-        // If no exact match is found, use the most recent Sunday event.
-        if let Some(ev) = events.iter().rev().find(|ev| ev.date <= input_date) {
+        return;
+    }
+
+    if let Some(date_arg) = &args.set_for {
+        // Fast path: the set only depends on the liturgical year, so skip event generation.
+        let lit_year = compute_liturgical_year(parse_date_or_exit(date_arg));
+        println!("{}: {}", message("liturgical-year"), lit_year);
+        println!("{}: {}", message("set"), cycle.set(lit_year));
+        if args.explain_set {
+            explain_set_of(lit_year, cycle);
+        }
+        return;
+    }
+
+    if let Some(text) = &set_of {
+        // Like --set-for, this needs only the liturgical year, not its events.
+        let lit_year = match text.parse::<i32>() {
+            Ok(year) if text.bytes().all(|b| b.is_ascii_digit()) => year,
+            _ => compute_liturgical_year(exit_on_error(parse_date_arg(text, || {
+                reference_today(args.today.as_deref(), args.tz)
+            }))),
+        };
+        println!("{}", cycle.set(lit_year));
+        if args.explain_set {
+            explain_set_of(lit_year, cycle);
+        }
+        return;
+    }
+
+    if let Some(text) = &nearest {
+        let date = exit_on_error(parse_date_arg(text, || {
+            reference_today(args.today.as_deref(), args.tz)
+        }));
+        let events = exit_on_error(generate_events(compute_liturgical_year(date), &options));
+        let previous = previous_event(&events, date)
+            .expect("a liturgical year opens with Advent I, on or before any date it contains");
+        let next = exit_on_error(next_event_from(date, &options));
+        let surrounding = [
+            (format!("{}:", message("previous")), previous),
+            (format!("{}:", message("next")), &next),
+        ];
+        let caption_width = surrounding
+            .iter()
+            .map(|(caption, _)| UnicodeWidthStr::width(caption.as_str()))
+            .max()
+            .unwrap_or(0);
+        let label_width = surrounding
+            .iter()
+            .map(|(_, ev)| UnicodeWidthStr::width(label_text(&ev.label).as_str()))
+            .max()
+            .unwrap_or(0);
+        let color_width = surrounding
+            .iter()
+            .map(|(_, ev)| UnicodeWidthStr::width(colors.text(ev.altar_color).as_str()))
+            .max()
+            .unwrap_or(0);
+        for (caption, ev) in &surrounding {
             println!(
-                "Note: {} is not an exact event date. Using readings for {} ({}).",
-                input_date.format("%d/%m/%Y"),
-                ev.label,
-                ev.date.format("%d/%m/%Y")
+                "{} {}  {}  {}  ({})",
+                pad_to_width(caption, caption_width),
+                ev.date.format("%d/%m/%Y"),
+                pad_to_width(&label_text(&ev.label), label_width),
+                colors.render_padded(ev.altar_color, color_width),
+                days_away_text((ev.date - date).num_days())
             );
-            println!("Liturgical Year: {}", lit_year);
-            println!("Set: {}", set);
-            println!("Pericope: {}", ev.label);
-            println!("Altar Color: {}", ev.altar_color);
-            println!("Readings:");
-            let gospel_set = if set == 3 { 1 } else { set + 1 };
-            println!(
-                "  Old Testament: Old Testament reading for {} (Set {})",
-                ev.label, set
+        }
+        return;
+    }
+
+    if let Some(year) = stats {
+        let histogram = exit_on_error(color_histogram(year, &options));
+        let mut counts: Vec<(AltarColor, usize)> = AltarColor::ALL
+            .into_iter()
+            .filter_map(|color| histogram.get(&color).map(|&count| (color, count)))
+            .collect();
+        counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        let line: Vec<String> = counts
+            .iter()
+            .map(|&(color, count)| format!("{}: {}", color_name(color), count))
+            .collect();
+        println!("{}", line.join(", "));
+        return;
+    }
+
+    if let Some((year, season)) = &season_sundays {
+        let season = exit_on_error(season.parse::<Season>());
+        println!(
+            "{}",
+            exit_on_error(season_sunday_count(*year, season, &options))
+        );
+        return;
+    }
+
+    if let Some((year, label)) = &when {
+        match exit_on_error(date_for_label(*year, label, &options)) {
+            Some(date) => println!("{}", date.format("%d/%m/%Y")),
+            None => fail(
+                "no-event",
+                fill_message(
+                    "no-event",
+                    &[("label", label.clone()), ("year", year.to_string())],
+                ),
+            ),
+        }
+        return;
+    }
+
+    if let Some((label, set)) = &label_readings {
+        if !cycle.contains(*set) {
+            fail(
+                "invalid-set",
+                fill_message(
+                    "invalid-set",
+                    &[
+                        ("set", set.to_string()),
+                        ("length", cycle.length.to_string()),
+                    ],
+                ),
             );
-            println!(
-                "  Lection:       Lection reading for {} (Set {})",
-                ev.label, set
+        }
+        let Some(label) = known_label(label, &options) else {
+            fail(
+                "unknown-label",
+                fill_message("unknown-label", &[("label", label.clone())]),
             );
-            println!(
-                "  Gospel:        Gospel reading for {} (Set {})",
-                ev.label, gospel_set
+        };
+        let readings = reading_provider
+            .readings(&label, *set)
+            .or_else(|| placeholders.readings(&label, *set))
+            .unwrap_or_else(|| {
+                fail(
+                    "no-readings",
+                    fill_message(
+                        "no-readings",
+                        &[("label", label.clone()), ("set", set.to_string())],
+                    ),
+                )
+            });
+        println!("{}: {}", message("pericope"), label_text(&label));
+        println!("{}: {}", message("set"), set);
+        println!("{}:", message("readings"));
+        print_readings(&readings);
+        return;
+    }
+
+    if let Some(date_arg) = &args.season_only {
+        let date = parse_date_or_exit(date_arg);
+        match exit_on_error(season_for_date(date, &options)) {
+            Some(season) => println!("{}", season_name(season)),
+            None => fail(
+                "no-season",
+                fill_message(
+                    "no-season",
+                    &[("date", date.format("%d/%m/%Y").to_string())],
+                ),
+            ),
+        }
+        return;
+    }
+
+    if args.self_check {
+        let year = args.year.expect("clap requires --year with --self-check");
+        let unadjusted = CalendarOptions {
+            color_overrides: Vec::new(),
+            ..options.clone()
+        };
+        let events = exit_on_error(generate_events(year, &unadjusted));
+        match validate_events(&events) {
+            Ok(()) => println!(
+                "All {} events of {} have their expected colors.",
+                events.len(),
+                year
+            ),
+            Err(deviations) => fail("self-check", deviations),
+        }
+        return;
+    }
+
+    if let Some(path) = &args.ics {
+        let year = args.year.expect("clap requires --year with --ics");
+        let events = exit_on_error(generate_events(year, &options));
+        let calendar = ics_calendar(&events, Utc::now());
+        if let Err(e) = std::fs::write(path, calendar) {
+            fail(
+                "io",
+                format!("Unable to write iCalendar file {}: {}", path, e),
             );
-            println!(
-                "  Preaching:     Preaching reading for {} (Set {})",
-                ev.label, set
+        }
+        return;
+    }
+
+    if args.legend {
+        let year = args.year.expect("clap requires --year with --legend");
+        let events = exit_on_error(generate_events(year, &options));
+        if let Some(palette) = &palette {
+            warn_missing_colors(palette, events.iter().map(|ev| ev.altar_color));
+        }
+        print_color_legend(&events, palette.as_ref().unwrap_or(&Palette::default()));
+        return;
+    }
+
+    if args.readings_matrix {
+        let year = args
+            .year
+            .expect("clap requires --year with --readings-matrix");
+        let events = exit_on_error(generate_events(year, &options));
+        print_readings_matrix(&events, &reading_provider);
+        return;
+    }
+
+    if args.count_sundays {
+        let year = args
+            .year
+            .expect("clap requires --year with --count-sundays");
+        println!("{}", exit_on_error(sunday_count(year, &options)));
+        return;
+    }
+
+    if let Some(year) = args.compare_easter_algorithms {
+        print_easter_comparison(year);
+        return;
+    }
+
+    if let Some(text) = &month {
+        let (start, end) = parse_month(text).unwrap_or_else(|e| fail("invalid-month", e));
+        let mut events = exit_on_error(events_in_range(start, end, &options));
+        retain_min_priority(&mut events, args.min_priority);
+        match args.format {
+            OutputFormat::Csv => exit_on_error(print_csv(&events, cycle)),
+            OutputFormat::Markdown => print_markdown_table(&events, cycle),
+            OutputFormat::Json => print_json(
+                &listing_records(events, palette.as_ref(), &options),
+                args.json_pretty,
+            ),
+            OutputFormat::Ics => print!("{}", ics_calendar(&events, Utc::now())),
+            _ => print_dated_event_list(&events, &list_style, "%a %e"),
+        }
+        return;
+    }
+
+    if let Some((start, end)) = &range {
+        let start = parse_date_or_exit(start);
+        let end = parse_date_or_exit(end);
+        if start > end {
+            fail(
+                "invalid-range",
+                fill_message(
+                    "invalid-range",
+                    &[
+                        ("start", start.format("%d/%m/%Y").to_string()),
+                        ("end", end.format("%d/%m/%Y").to_string()),
+                    ],
+                ),
             );
+        }
+        let mut events = exit_on_error(events_in_range(start, end, &options));
+        retain_min_priority(&mut events, args.min_priority);
+        match args.format {
+            OutputFormat::Csv => exit_on_error(print_csv(&events, cycle)),
+            OutputFormat::Markdown => print_markdown_table(&events, cycle),
+            OutputFormat::Json => print_json(
+                &listing_records(events, palette.as_ref(), &options),
+                args.json_pretty,
+            ),
+            OutputFormat::Ics => print!("{}", ics_calendar(&events, Utc::now())),
+            _ => print_event_list(&events, &list_style),
+        }
+        return;
+    }
+
+    if let Some(year) = args.civil_year {
+        let mut events = exit_on_error(civil_year_events(year, &options));
+        retain_min_priority(&mut events, args.min_priority);
+        print_event_list(&events, &list_style);
+        return;
+    }
+
+    if let (true, Some(year)) = (args.date.is_empty(), args.year) {
+        let mut events = exit_on_error(generate_events(year, &options));
+        retain_min_priority(&mut events, args.min_priority);
+        match args.format {
+            OutputFormat::Text => print_event_list(&events, &list_style),
+            OutputFormat::Chart => print_chart(year, events, &options),
+            OutputFormat::Csv => exit_on_error(print_csv(&events, cycle)),
+            OutputFormat::Markdown => print_markdown_table(&events, cycle),
+            OutputFormat::Jsonl => {
+                for record in listing_records(events, palette.as_ref(), &options) {
+                    print_json(&record, false);
+                }
+            }
+            OutputFormat::Json => print_json(
+                &listing_records(events, palette.as_ref(), &options),
+                args.json_pretty,
+            ),
+            OutputFormat::Ics => print!("{}", ics_calendar(&events, Utc::now())),
+            OutputFormat::Bulletin | OutputFormat::Summary => {
+                fail(
+                    "unsupported-format",
+                    fill_message(
+                        "lookup-only-format",
+                        &[("format", args.format.name().to_string())],
+                    ),
+                );
+            }
+        }
+        return;
+    }
+
+    if matches!(
+        args.format,
+        OutputFormat::Chart
+            | OutputFormat::Jsonl
+            | OutputFormat::Csv
+            | OutputFormat::Markdown
+            | OutputFormat::Ics
+    ) {
+        fail(
+            "unsupported-format",
+            fill_message(
+                "year-only-format",
+                &[("format", args.format.name().to_string())],
+            ),
+        );
+    }
+
+    let lookup_context = LookupContext {
+        options: &options,
+        readings: &reading_provider,
+        placeholders,
+        cycle,
+        palette: palette.as_ref(),
+        colors: &colors,
+        format: args.format,
+        json_pretty: args.json_pretty,
+        verbose: args.verbose,
+        explain_set: args.explain_set,
+        years: RefCell::new(CalendarCache::new(options.clone())),
+        printed: Cell::new(false),
+    };
+
+    if let Some(path) = &args.dates_file {
+        let text = exit_on_error(read_dates_input(path));
+        if !run_dates_file(&text, &lookup_context) {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Parse the input dates.
+    let input_dates: Vec<NaiveDate> = if args.date.is_empty() {
+        // Noted on stderr so piped and JSON output is unaffected.
+        if !JSON_ERRORS.load(Ordering::Relaxed) {
+            eprintln!("{}", message("using-today"));
+        }
+        vec![reference_today(args.today.as_deref(), args.tz)]
+    } else {
+        args.date
+            .iter()
+            .map(|date_arg| {
+                exit_on_error(parse_date_arg(date_arg, || {
+                    reference_today(args.today.as_deref(), args.tz)
+                }))
+            })
+            .collect()
+    };
+
+    if args.is_feast {
+        let all_events = input_dates.iter().all(|&date| {
+            let mut events =
+                exit_on_error(generate_events(compute_liturgical_year(date), &options));
+            retain_min_priority(&mut events, args.min_priority);
+            is_event_date(&events, date)
+        });
+        std::process::exit(if all_events { 0 } else { 1 });
+    }
+
+    for input_date in input_dates {
+        if args.week {
+            let mut events = exit_on_error(events_in_range(
+                input_date,
+                input_date + Duration::days(6),
+                &options,
+            ));
+            retain_min_priority(&mut events, args.min_priority);
+            print_event_list(&events, &list_style);
+        } else if args.next || args.prev {
+            let ev = if args.next {
+                exit_on_error(next_event_from(input_date, &options))
+            } else {
+                let events = exit_on_error(generate_events(
+                    compute_liturgical_year(input_date),
+                    &options,
+                ));
+                previous_event(&events, input_date)
+                    .expect(
+                        "a liturgical year opens with Advent I, on or before any date it contains",
+                    )
+                    .clone()
+            };
+            print_event_list(&[ev], &list_style);
         } else {
-            println!(
-                "No pericope event found for {} in the liturgical year {}.",
-                input_date.format("%d/%m/%Y"),
-                lit_year
-            );
+            exit_on_error(lookup_context.print(input_date));
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_date_depends_on_the_parish_time_zone() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        let los_angeles: Tz = "America/Los_Angeles".parse().unwrap();
+        let date = |day, month| NaiveDate::from_ymd_opt(2025, month, day).unwrap();
+
+        // 07:30 UTC on Sunday 9 March 2025 is still Saturday evening in Los Angeles,
+        // which only switches to summer time at 10:00 UTC that day.
+        let instant = "2025-03-09T07:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(civil_date_in(instant, berlin), date(9, 3));
+        assert_eq!(civil_date_in(instant, los_angeles), date(8, 3));
+
+        // A day later, after the change, Los Angeles is seven hours behind UTC.
+        let instant = "2025-03-10T06:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(civil_date_in(instant, berlin), date(10, 3));
+        assert_eq!(civil_date_in(instant, los_angeles), date(9, 3));
+        let instant = "2025-03-10T07:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(civil_date_in(instant, los_angeles), date(10, 3));
+    }
+}
@@ -0,0 +1,609 @@
+//! End-to-end tests of the `pericope` binary.
+
+use std::process::{Command, Output};
+
+/// Runs the binary with `args` and a fixed "today", so no test depends on the clock.
+fn pericope(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pericope"))
+        .args(args)
+        .env("LITCAL_TODAY", "01/01/2025")
+        .output()
+        .expect("the binary runs")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).expect("output is UTF-8")
+}
+
+/// Runs the binary and parses its stdout as a single JSON document.
+fn json(args: &[&str]) -> serde_json::Value {
+    serde_json::from_str(&stdout(&pericope(args))).expect("stdout is JSON")
+}
+
+/// Writes `content` to a file unique to the calling test and returns its path.
+fn temp_file(name: &str, content: &str) -> String {
+    let path = std::env::temp_dir().join(format!("pericope-cli-{}-{}", std::process::id(), name));
+    std::fs::write(&path, content).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn json_errors_keep_the_calendar_error_kind() {
+    let output = pericope(&["--json", "01/01/1500"]);
+    assert!(!output.status.success());
+    let envelope: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(envelope["error"]["kind"], "historical-year");
+
+    let output = pericope(&["--json", "--year", "300000"]);
+    assert!(!output.status.success());
+    let envelope: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(envelope["error"]["kind"], "year-out-of-range");
+    assert_eq!(
+        envelope["error"]["message"],
+        "year 300000 is outside the supported range"
+    );
+}
+
+#[test]
+fn a_bad_date_argument_has_the_dates_file_kind() {
+    let output = pericope(&["--json", "31/02/2025"]);
+    assert!(!output.status.success());
+    let envelope: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(envelope["error"]["kind"], "invalid-date");
+    let path = temp_file("bad-date.txt", "31/02/2025\n");
+    let output = pericope(&["--json", "--dates-file", &path]);
+    let envelope: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(envelope["error"]["kind"], "invalid-date");
+}
+
+#[test]
+fn dates_file_errors_use_the_json_envelope() {
+    let path = temp_file("dates.txt", "01/01/1500\nnot a date\n01/01/2025\n");
+    let output = pericope(&["--json", "--dates-file", &path]);
+    assert!(!output.status.success());
+    let lines: Vec<serde_json::Value> = stdout(&output)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["error"]["kind"], "historical-year");
+    assert_eq!(lines[1]["error"]["kind"], "invalid-date");
+    assert!(lines[1]["error"]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("line 2:"));
+    assert_eq!(lines[2]["pericope"], "christmas + 1");
+}
+
+#[test]
+fn a_feast_on_a_sunday_keys_the_readings() {
+    // All Saints 2026 falls on "trinity + 22", a green Sunday.
+    let readings = temp_file(
+        "feast-readings.toml",
+        "[[reading]]\nlabel = \"all saints\"\nset = 2\nold_testament = \"Rev 7:9-17\"\n\
+         lection = \"1 John 3:1-3\"\ngospel = \"Mat 5:1-12\"\npreaching = \"Mat 5:1-12\"\n\n\
+         [[reading]]\nlabel = \"trinity + 22\"\nset = 2\nold_testament = \"Mic 6:6-8\"\n\
+         lection = \"Phil 1:3-11\"\ngospel = \"Mat 18:21-35\"\npreaching = \"Mat 18:21-35\"\n",
+    );
+    let plain = json(&["--json", "--readings", &readings, "01/11/2026"]);
+    assert_eq!(plain["pericope"], "trinity + 22");
+    assert_eq!(plain["readings"]["old_testament"], "Mic 6:6-8");
+
+    let feast = json(&[
+        "--json",
+        "--include-fixed-feasts",
+        "--readings",
+        &readings,
+        "01/11/2026",
+    ]);
+    assert_eq!(feast["set"], 2);
+    assert_eq!(feast["pericope"], "all saints");
+    assert_eq!(feast["readings"]["old_testament"], "Rev 7:9-17");
+}
+
+#[test]
+fn german_catalog_names_the_season() {
+    let output = pericope(&["--lang", "de", "--season-only", "15/02/2026"]);
+    assert_eq!(stdout(&output), "Vorpassions- und Passionszeit\n");
+    let output = pericope(&["--lang", "de", "15/02/2026"]);
+    assert!(stdout(&output).contains("Zeit: Vorpassions- und Passionszeit\n"));
+}
+
+#[test]
+fn unknown_season_in_a_catalog_is_rejected() {
+    let path = temp_file("seasons.toml", "[seasons]\nlent = \"Fastenzeit\"\n");
+    let output = pericope(&["--lang-file", &path, "15/02/2026"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Unknown season \"lent\""), "{}", stderr);
+}
+
+#[test]
+fn bulletin_heads_with_the_looked_up_date() {
+    // Ash Wednesday 2026 uses the readings of the Sunday before, "easter - 7".
+    let output = stdout(&pericope(&["--format", "bulletin", "18/02/2026"]));
+    assert!(output.starts_with("Wednesday 18 February 2026\nPre-Easter (Set 2)\n"));
+    let output = stdout(&pericope(&[
+        "--lang",
+        "de",
+        "--format",
+        "bulletin",
+        "18/02/2026",
+    ]));
+    assert!(
+        output.starts_with("Mittwoch, 18. Februar 2026\nVorpassions- und Passionszeit (Reihe 2)\n")
+    );
+}
+
+#[test]
+fn partial_catalog_falls_back_to_english() {
+    let path = temp_file(
+        "partial.toml",
+        "date = \"Datum\"\nset = \"Reihe\"\nprevious = \"Vorher\"\n",
+    );
+    let output = stdout(&pericope(&["--lang-file", &path, "15/02/2026"]));
+    assert!(output.starts_with("Datum: 15/02/2026\nLiturgical Year: 2025\nReihe: 2\n"));
+    let output = stdout(&pericope(&[
+        "--lang-file",
+        &path,
+        "--set-for",
+        "15/02/2026",
+    ]));
+    assert_eq!(output, "Liturgical Year: 2025\nReihe: 2\n");
+    let output = stdout(&pericope(&["--lang-file", &path, "nearest", "18/02/2026"]));
+    assert!(output.starts_with("Vorher: 15/02/2026"));
+    assert!(output.contains("\nNext:   22/02/2026"));
+    assert!(output.contains("(3 days ago)"));
+}
+
+#[test]
+fn german_catalog_translates_summaries_and_errors() {
+    let output = stdout(&pericope(&[
+        "--lang",
+        "de",
+        "--format",
+        "summary",
+        "18/02/2026",
+    ]));
+    assert_eq!(output, "18/02/2026 · Reihe II · Estomihi · weiß\n");
+    let output = pericope(&["--lang", "de", "readings", "foo", "--set", "1"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr,
+        "Fehler: „foo“ ist keine Bezeichnung eines Ereignisses.\n"
+    );
+}
+
+#[test]
+fn palette_feeds_the_color_legend() {
+    let path = temp_file("palette.toml", "purple = \"#5b2a86\"\nwhite = \"#fff\"\n");
+    let output = pericope(&["--palette", &path, "--legend", "--year", "2024"]);
+    let legend = stdout(&output);
+    assert!(
+        legend.contains("purple  #5b2a86  Advent, Pre-Easter\n"),
+        "{}",
+        legend
+    );
+    assert!(legend.contains("green   #2e7d32"), "{}", legend);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("palette does not define \"green\""));
+    assert!(!stderr.contains("\"purple\""));
+}
+
+/// Whether `text` has the "Book chapter:verse" shape of a scripture reference.
+fn is_reference(text: &str) -> bool {
+    let Some((book, place)) = text.rsplit_once(' ') else {
+        return false;
+    };
+    let Some((chapter, verses)) = place.split_once(':') else {
+        return false;
+    };
+    !book.is_empty()
+        && chapter.parse::<u32>().is_ok()
+        && verses.split('-').all(|verse| verse.parse::<u32>().is_ok())
+}
+
+#[test]
+fn stub_placeholders_parse_as_references() {
+    // "easter - 7" has no bundled readings in Set 1.
+    let lookup = json(&["--json", "--placeholder-style", "stub", "02/03/2025"]);
+    let readings = lookup["readings"].as_object().unwrap();
+    assert_eq!(readings.len(), 4);
+    for reading in readings.values() {
+        assert_eq!(reading, "TBD 0:0");
+        assert!(is_reference(reading.as_str().unwrap()));
+    }
+    // Bundled readings are kept.
+    let lookup = json(&["--json", "--placeholder-style", "stub", "01/12/2024"]);
+    assert_eq!(lookup["readings"]["gospel"], "Luk 21:25-36");
+    assert!(is_reference("Luk 21:25-36"));
+    assert!(!is_reference("Gospel reading for advent + 1 (Set 1)"));
+}
+
+#[test]
+fn pretty_and_compact_json_hold_the_same_data() {
+    for args in [&["--json", "08/02/2025"][..], &["--json", "list", "2024"]] {
+        let compact = stdout(&pericope(args));
+        let pretty = stdout(&pericope(&[args, &["--json-pretty"]].concat()));
+        assert_eq!(compact.lines().count(), 1);
+        assert!(pretty.lines().count() > 1);
+        let compact: serde_json::Value = serde_json::from_str(&compact).unwrap();
+        let pretty: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(compact, pretty);
+    }
+}
+
+#[test]
+fn color_codes_cover_every_color() {
+    let config = temp_file(
+        "rose.toml",
+        "[[color_override]]\nlabel = \"advent + 2\"\ncolor = \"rose\"\n",
+    );
+    let listing = stdout(&pericope(&[
+        "--config",
+        &config,
+        "--color-format",
+        "code",
+        "list",
+        "2024",
+    ]));
+    for line in [
+        "01/12/2024  advent + 1       P\n",
+        "08/12/2024  advent + 2       Ro\n",
+        "25/12/2024  christmas        W\n",
+        "12/01/2025  epiphany + 1     G\n",
+        "08/06/2025  pentecost        R\n",
+    ] {
+        assert!(
+            listing.contains(line),
+            "{:?} missing from\n{}",
+            line,
+            listing
+        );
+    }
+    let summary = stdout(&pericope(&[
+        "--color-format",
+        "code",
+        "--format",
+        "summary",
+        "08/06/2025",
+    ]));
+    assert_eq!(summary, "08/06/2025 · Set I · pentecost · R\n");
+}
+
+#[test]
+fn set_for_matches_a_full_lookup() {
+    for date in [
+        "23/11/2024",
+        "24/11/2024",
+        "01/01/2030",
+        "15/08/2041",
+        "30/11/2099",
+    ] {
+        let lookup = json(&["--json", date]);
+        let quick = stdout(&pericope(&["--set-for", date]));
+        assert_eq!(
+            quick,
+            format!(
+                "Liturgical Year: {}\nSet: {}\n",
+                lookup["liturgical_year"], lookup["set"]
+            ),
+            "{}",
+            date
+        );
+    }
+}
+
+#[test]
+fn summary_is_one_dotted_line() {
+    let output = stdout(&pericope(&[
+        "--format",
+        "summary",
+        "08/02/2025",
+        "09/02/2025",
+    ]));
+    assert_eq!(
+        output,
+        "08/02/2025 · Set I · epiphany + 4 · green\n09/02/2025 · Set I · epiphany + 5 · green\n"
+    );
+}
+
+#[test]
+fn chart_aligns_one_row_per_event() {
+    let chart = stdout(&pericope(&["--format", "chart", "--year", "2024"]));
+    let lines: Vec<&str> = chart.lines().collect();
+    assert_eq!(lines[0], "Week  Date        Season  Color  Pericope");
+    assert_eq!(lines[1], "1     24/11/2024  Adv     P      advent");
+    assert_eq!(lines[6], "5     25/12/2024  Chr     W      christmas");
+    assert!(lines.contains(&"29    08/06/2025  Pen     R      pentecost"));
+    assert_eq!(lines.len(), 54);
+}
+
+#[test]
+fn season_only_names_each_season() {
+    for (date, season) in [
+        ("04/12/2024", "Advent"),
+        ("29/12/2024", "Christmas"),
+        ("12/01/2025", "Epiphany"),
+        ("16/03/2025", "Pre-Easter"),
+        ("27/04/2025", "Easter"),
+        ("08/06/2025", "Pentecost"),
+        ("13/07/2025", "Trinity"),
+        ("23/11/2025", "Advent"),
+    ] {
+        let output = pericope(&["--season-only", date]);
+        assert!(output.status.success(), "{}", date);
+        assert_eq!(stdout(&output), format!("{}\n", season), "{}", date);
+    }
+}
+
+#[test]
+fn json_flags_principal_feasts() {
+    assert_eq!(json(&["--json", "20/04/2025"])["is_principal"], true);
+    assert_eq!(json(&["--json", "13/07/2025"])["is_principal"], false);
+}
+
+#[test]
+fn warn_overrides_reports_a_shadowed_default() {
+    let readings = temp_file(
+        "override-readings.toml",
+        "[[reading]]\nlabel = \"easter\"\nset = 1\nold_testament = \"Isa 25:6-9\"\n\
+         lection = \"1 Cor 5:6-8\"\ngospel = \"Mar 16:1-8\"\npreaching = \"Mar 16:1-8\"\n",
+    );
+    let output = pericope(&["--warn-overrides", "--readings", &readings, "20/04/2025"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "readings file overrides easter (Set 1): Old Testament \"Job 19:23-27\" -> \"Isa 25:6-9\""
+        ),
+        "{}",
+        stderr
+    );
+    // Without the flag the override is silent.
+    let output = pericope(&["--readings", &readings, "20/04/2025"]);
+    assert!(output.stderr.is_empty());
+    assert!(stdout(&output).contains("Isa 25:6-9"));
+}
+
+#[test]
+fn readings_matrix_puts_each_entry_in_its_set_column() {
+    let readings = temp_file(
+        "matrix-readings.toml",
+        "[[reading]]\nlabel = \"trinity + 4\"\nset = 2\nold_testament = \"Gen 50:15-21\"\n\
+         lection = \"Rom 12:17-21\"\ngospel = \"Luk 6:36-42\"\npreaching = \"Luk 6:36-42\"\n",
+    );
+    let matrix = stdout(&pericope(&[
+        "--readings-matrix",
+        "--year",
+        "2024",
+        "--readings",
+        &readings,
+    ]));
+    let row = |label: &str| -> Vec<String> {
+        let line = matrix
+            .lines()
+            .find(|line| line.starts_with(&format!("{}  ", label)))
+            .unwrap_or_else(|| panic!("no row for {}", label));
+        line.split("  ")
+            .map(str::trim)
+            .filter(|cell| !cell.is_empty())
+            .map(String::from)
+            .collect()
+    };
+    assert_eq!(
+        matrix
+            .lines()
+            .next()
+            .unwrap()
+            .split_whitespace()
+            .collect::<Vec<_>>(),
+        ["Pericope", "Set", "I", "Set", "II", "Set", "III"]
+    );
+    assert_eq!(
+        row("easter - 9"),
+        [
+            "easter - 9",
+            "Jer 1:4-10; 1 Cor:09:24-10:05; Mat 19:27-30; Mat 20:1-16",
+            "(placeholder)",
+            "(placeholder)"
+        ]
+    );
+    assert_eq!(
+        row("trinity + 4"),
+        [
+            "trinity + 4",
+            "(placeholder)",
+            "Gen 50:15-21; Rom 12:17-21; Luk 6:36-42; Luk 6:36-42",
+            "(placeholder)"
+        ]
+    );
+}
+
+#[test]
+fn compare_easter_algorithms_reports_the_gap() {
+    // 2024: Western Easter on 31 March, Orthodox Easter five weeks later.
+    assert_eq!(
+        stdout(&pericope(&["--compare-easter-algorithms", "2024"])),
+        "Gregorian: 31/03/2024\nJulian:    05/05/2024\nDays apart: 35\n"
+    );
+    // 2025: both fall on 20 April.
+    assert!(
+        stdout(&pericope(&["--compare-easter-algorithms", "2025"])).ends_with("Days apart: 0\n")
+    );
+}
+
+#[test]
+fn litcal_today_drives_next_unless_today_is_given() {
+    let next_with = |today: &str, args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_pericope"))
+            .args(args)
+            .arg("--next")
+            .env("LITCAL_TODAY", today)
+            .output()
+            .expect("the binary runs");
+        stdout(&output)
+    };
+    assert!(next_with("15/07/2025", &[]).starts_with("20/07/2025  trinity + 5"));
+    assert!(next_with("01/01/2025", &[]).starts_with("05/01/2025  epiphany"));
+    assert!(
+        next_with("15/07/2025", &["--today", "01/12/2024"]).starts_with("08/12/2024  advent + 2")
+    );
+}
+
+#[test]
+fn jsonl_prints_one_event_object_per_line() {
+    let text = stdout(&pericope(&["--format", "jsonl", "--year", "2024"]));
+    let events: Vec<serde_json::Value> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each line is JSON"))
+        .collect();
+    assert_eq!(events.len(), 53);
+    for event in &events {
+        for field in ["id", "date", "label", "color", "hex", "season", "week"] {
+            assert!(event.get(field).is_some(), "{} lacks {}", event, field);
+        }
+    }
+    assert_eq!(events[0]["label"], "advent");
+    assert_eq!(events[0]["date"], "2024-11-24");
+    assert_eq!(events[52]["label"], "trinity + 22");
+}
+
+#[test]
+fn min_priority_keeps_only_the_feasts() {
+    let labels = |min_priority: &str| -> Vec<String> {
+        stdout(&pericope(&[
+            "--year",
+            "2024",
+            "--include-fixed-feasts",
+            "--include-movable-feasts",
+            "--min-priority",
+            min_priority,
+        ]))
+        .lines()
+        .map(|line| line.split("  ").nth(1).unwrap().trim().to_string())
+        .collect()
+    };
+    assert_eq!(
+        labels("8"),
+        [
+            "epiphany day",
+            "candlemas",
+            "transfiguration",
+            "ash wednesday",
+            "maundy thursday",
+            "good friday",
+            "ascension",
+            "michaelmas",
+            "reformation day",
+            "all saints"
+        ]
+    );
+    assert_eq!(labels("9"), ["candlemas"]);
+    assert!(labels("0").len() > labels("8").len());
+}
+
+#[test]
+fn dates_file_skips_comments_and_keeps_going_after_a_bad_line() {
+    let path = temp_file(
+        "mixed-dates.txt",
+        "# first Sunday of Advent\n24/11/2024\n\n  # indented comment\n31/02/2025\n20/04/2025\n",
+    );
+    let output = pericope(&["--dates-file", &path]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(
+        stderr,
+        "Error: line 5: Unable to parse date. Please use dd/mm/yyyy or yyyy-mm-dd format.\n"
+    );
+    let text = stdout(&output);
+    let pericopes: Vec<&str> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("Pericope: "))
+        .collect();
+    assert_eq!(pericopes, ["advent", "easter"]);
+}
+
+#[test]
+fn legend_lists_the_colors_of_the_year() {
+    assert_eq!(
+        stdout(&pericope(&["--legend", "--year", "2024"])),
+        "Color   Hex      Seasons\n\
+         purple  #663399  Advent, Pre-Easter\n\
+         white   #ffffff  Christmas, Epiphany, Pre-Easter, Easter, Trinity\n\
+         green   #2e7d32  Epiphany, Pre-Easter, Trinity\n\
+         red     #c62828  Pentecost, Trinity\n"
+    );
+}
+
+#[test]
+fn cycle_length_changes_the_set_of_a_date() {
+    // Liturgical year 2025, the second year after the anchor.
+    assert_eq!(json(&["--json", "20/04/2026"])["set"], 2);
+    assert_eq!(
+        json(&["--json", "--cycle-length", "2", "20/04/2026"])["set"],
+        2
+    );
+    assert_eq!(
+        json(&["--json", "--cycle-length", "1", "20/04/2026"])["set"],
+        1
+    );
+    assert_eq!(
+        json(&["--json", "--cycle-length", "2", "12/04/2027"])["set"],
+        1
+    );
+}
+
+#[test]
+fn lookup_json_carries_the_anchor_offset() {
+    let christmas = json(&["--json", "25/12/2024"]);
+    assert_eq!(christmas["anchor"], "advent");
+    assert_eq!(christmas["offset"], 4);
+    let trinity = json(&["--json", "15/06/2025"]);
+    assert_eq!(trinity["anchor"], "easter");
+    assert_eq!(trinity["offset"], 8);
+    assert_eq!(trinity["weeks_from_easter"], 8);
+}
+
+#[test]
+fn german_legend_translates_the_color_names() {
+    let legend = stdout(&pericope(&["--lang", "de", "--legend", "--year", "2024"]));
+    let colors: Vec<&str> = legend
+        .lines()
+        .skip(1)
+        .map(|line| line.split_whitespace().next().unwrap())
+        .collect();
+    assert_eq!(colors, ["violett", "weiß", "grün", "rot"]);
+    // The hex column stays aligned after the two-byte "ß" and "ü".
+    assert!(legend.contains("\nweiß     #ffffff  "), "{}", legend);
+    assert!(legend.contains("\ngrün     #2e7d32  "), "{}", legend);
+}
+
+#[test]
+fn nearest_aligns_the_distance_column() {
+    for args in [
+        &["nearest", "10/06/2025"][..],
+        &["--lang", "de", "nearest", "22/11/2025"][..],
+    ] {
+        let output = stdout(&pericope(args));
+        let columns: Vec<usize> = output
+            .lines()
+            .map(|line| line.chars().position(|c| c == '(').unwrap())
+            .collect();
+        assert_eq!(columns.len(), 2, "{}", output);
+        assert_eq!(columns[0], columns[1], "{}", output);
+    }
+    assert_eq!(
+        stdout(&pericope(&["nearest", "10/06/2025"])),
+        "Previous: 08/06/2025  pentecost  red    (2 days ago)\n\
+         Next:     15/06/2025  trinity    white  (in 5 days)\n"
+    );
+}
+
+#[test]
+fn an_extreme_set_anchor_still_gives_a_set() {
+    let output = pericope(&["--set-anchor=-2147483648", "--set-for", "15/02/2026"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).ends_with("Set: 3\n"), "{}", stdout(&output));
+}